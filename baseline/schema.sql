@@ -0,0 +1,280 @@
+-- Schema of the database after the last 2024 migration, 2024-10-08-005616_overview_width_changes.
+--
+-- The 2024 migrations were applied to the existing databases one at a time, by hand, and a
+-- strict replay of them in order does not get past db_refactoring00: it creates tables an
+-- earlier migration already created. The files are history and are left as they are. An
+-- empty database is created from this file instead, `migrate up` then records the 2024
+-- migrations as applied and runs the later ones on top of it.
+
+-- From 00000000000000_diesel_initial_setup.
+CREATE OR REPLACE FUNCTION diesel_manage_updated_at(_tbl regclass) RETURNS VOID AS $$
+BEGIN
+    EXECUTE format('CREATE TRIGGER set_updated_at BEFORE UPDATE ON %s
+                    FOR EACH ROW EXECUTE PROCEDURE diesel_set_updated_at()', _tbl);
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION diesel_set_updated_at() RETURNS trigger AS $$
+BEGIN
+    IF (
+        NEW IS DISTINCT FROM OLD AND
+        NEW.updated_at IS NOT DISTINCT FROM OLD.updated_at
+    ) THEN
+        NEW.updated_at := current_timestamp;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+create table symbols
+(
+    sid         bigint primary key,
+    symbol      text      not null,
+    name        text      not null,
+    sec_type    text      not null,
+    region      text      not null,
+    marketopen  time      not null,
+    marketclose time      not null,
+    timezone    text      not null,
+    currency    text      not null,
+    overview    boolean   not null,
+    intraday    boolean   not null,
+    summary     boolean   not null,
+    c_time      timestamp not null,
+    m_time      timestamp not null
+);
+
+create table overviews
+(
+    sid                  bigint primary key references symbols (sid),
+    symbol               text             not null,
+    name                 text             not null,
+    description          text             not null,
+    cik                  text             not null,
+    exch                 text             not null,
+    curr                 text             not null,
+    country              text             not null,
+    sector               text             not null,
+    industry             text             not null,
+    address              text             not null,
+    fiscalyearend        text             not null,
+    latestquarter        date             not null,
+    marketcapitalization bigint           not null,
+    ebitda               bigint           not null,
+    peratio              real             not null,
+    pegratio             real             not null,
+    bookvalue            double precision not null,
+    dividendpershare     real             not null,
+    dividendyield        real             not null,
+    eps                  real             not null,
+    c_time               timestamp        not null,
+    mod_time             timestamp        not null
+);
+
+create table overviewexts
+(
+    sid                        bigint primary key references symbols (sid),
+    revenuepersharettm         real             not null,
+    profitmargin               real             not null,
+    operatingmarginttm         real             not null,
+    returnonassetsttm          real             not null,
+    returnonequityttm          real             not null,
+    revenuettm                 bigint           not null,
+    grossprofitttm             bigint           not null,
+    dilutedepsttm              real             not null,
+    quarterlyearningsgrowthyoy real             not null,
+    quarterlyrevenuegrowthyoy  real             not null,
+    analysttargetprice         real             not null,
+    trailingpe                 real             not null,
+    forwardpe                  real             not null,
+    pricetosalesratiottm       real             not null,
+    pricetobookratio           real             not null,
+    evtorevenue                real             not null,
+    evtoebitda                 real             not null,
+    beta                       double precision not null,
+    annweekhigh                double precision not null,
+    annweeklow                 double precision not null,
+    fiftydaymovingaverage      double precision not null,
+    twohdaymovingaverage       double precision not null,
+    sharesoutstanding          double precision not null,
+    dividenddate               date             not null,
+    exdividenddate             date             not null,
+    c_time                     timestamp        not null,
+    mod_time                   timestamp        not null
+);
+
+create table intradayprices
+(
+    eventid serial primary key,
+    tstamp  timestamp not null,
+    sid     bigint    not null references symbols (sid),
+    symbol  text      not null,
+    open    real      not null,
+    high    real      not null,
+    low     real      not null,
+    close   real      not null,
+    volume  integer   not null
+);
+
+create table summaryprices
+(
+    eventid serial primary key,
+    date    date    not null,
+    sid     bigint  not null references symbols (sid),
+    symbol  text    not null,
+    open    real    not null,
+    high    real    not null,
+    low     real    not null,
+    close   real    not null,
+    volume  integer not null
+);
+
+create table topstats
+(
+    eventid    serial primary key,
+    date       timestamp not null unique,
+    event_type text      not null,
+    sid        bigint    not null references symbols (sid),
+    symbol     text      not null,
+    price      real      not null,
+    change_val real      not null,
+    change_pct real      not null,
+    volume     integer   not null,
+    constraint topstats_date_event_type_unique unique (date, event_type, sid),
+    constraint topstats_pk unique (sid, event_type, date)
+);
+
+create table newsoverviews
+(
+    id       serial primary key,
+    sid      bigint    not null references symbols (sid),
+    items    integer   not null,
+    hashid   text      not null,
+    creation timestamp not null,
+    constraint unique_creation_sid unique (hashid, sid)
+);
+
+create table authors
+(
+    id          serial primary key,
+    author_name text not null unique
+);
+
+create table sources
+(
+    id          serial primary key,
+    source_name text not null,
+    domain      text not null
+);
+
+create table articles
+(
+    hashid   text primary key,
+    sourceid integer   not null references sources (id),
+    category text      not null,
+    title    text      not null,
+    url      text      not null,
+    summary  text      not null,
+    banner   text      not null,
+    author   integer   not null references authors (id),
+    ct       timestamp not null
+);
+
+create table feeds
+(
+    id             serial primary key,
+    sid            bigint           not null references symbols (sid),
+    newsoverviewid integer          not null references newsoverviews (id),
+    articleid      text             not null references articles (hashid),
+    sourceid       integer          not null references sources (id),
+    osentiment     double precision not null,
+    sentlabel      text             not null
+);
+
+create table authormaps
+(
+    id       serial primary key,
+    feedid   integer not null references feeds (id),
+    authorid integer not null references authors (id),
+    unique (feedid, authorid)
+);
+
+create table tickersentiments
+(
+    id              serial primary key,
+    feedid          integer          not null references feeds (id),
+    sid             bigint           not null references symbols (sid),
+    relevance       double precision not null,
+    tsentiment      double precision not null,
+    sentimentlable  text             not null
+);
+
+create table topicrefs
+(
+    id   serial primary key,
+    name text not null unique
+);
+
+create table topicmaps
+(
+    id       serial primary key,
+    sid      bigint           not null references symbols (sid),
+    feedid   integer          not null references feeds (id),
+    topicid  integer          not null references topicrefs (id),
+    relscore double precision not null
+);
+
+create table proctypes
+(
+    id   serial primary key,
+    name text not null unique
+);
+
+create table states
+(
+    id   serial primary key,
+    name text not null unique
+);
+
+create table procstates
+(
+    spid       serial primary key,
+    proc_id    integer references proctypes (id),
+    start_time timestamp not null default now(),
+    end_state  integer references states (id) default null,
+    end_time   timestamp default null
+);
+
+insert into authors (author_name) values
+('NONE');
+
+insert into topicrefs (name) values
+('Blockchain'),
+('Earnings'),
+('Economy - Fiscal'),
+('Economy - Macro'),
+('Economy - Monetary'),
+('Energy & Transportation'),
+('Finance'),
+('Financial Markets'),
+('IPO'),
+('Life Sciences'),
+('Manufacturing'),
+('Real Estate & Construction'),
+('Retail & Wholesale'),
+('Technology');
+
+insert into proctypes (name) values
+('load_intraday'),
+('load_missed'),
+('load_missed_overviews'),
+('load_news'),
+('load_open_close'),
+('load_overviews'),
+('load_symbols'),
+('load_tops');
+
+insert into states (name) values
+('running'),
+('success'),
+('failed');
@@ -0,0 +1,21 @@
+update symbols
+set timezone = 'UTC-04'
+where sec_type = 'Crypto';
+
+alter table newsoverviews
+    alter column creation type timestamp using creation::timestamp;
+
+alter table articles
+    alter column ct type timestamp using ct at time zone 'America/New_York';
+
+alter table topstats
+    alter column date type timestamp using date at time zone 'America/New_York';
+
+update intradayprices i
+set tstamp = (i.tstamp at time zone 'UTC') at time zone 'America/New_York'
+from symbols s
+where s.sid = i.sid
+  and s.sec_type = 'Crypto';
+
+alter table intradayprices
+    alter column tstamp type timestamp using tstamp at time zone 'America/New_York';
@@ -0,0 +1,26 @@
+-- Store ingested timestamps as UTC instants.
+-- Intraday bars, top stats and article publication times were stored as US/Eastern wall clock
+-- times, except for crypto bars which Alpha Vantage reports in UTC.
+
+alter table intradayprices
+    alter column tstamp type timestamptz using tstamp at time zone 'America/New_York';
+
+update intradayprices i
+set tstamp = (i.tstamp at time zone 'America/New_York') at time zone 'UTC'
+from symbols s
+where s.sid = i.sid
+  and s.sec_type = 'Crypto';
+
+alter table topstats
+    alter column date type timestamptz using date at time zone 'America/New_York';
+
+alter table articles
+    alter column ct type timestamptz using ct at time zone 'America/New_York';
+
+-- creation was the loader's local midnight; interpret it in the session time zone.
+alter table newsoverviews
+    alter column creation type timestamptz using creation::timestamptz;
+
+update symbols
+set timezone = 'UTC'
+where sec_type = 'Crypto';
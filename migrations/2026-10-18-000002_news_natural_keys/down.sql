@@ -0,0 +1,8 @@
+alter table topicmaps
+    drop constraint if exists topicmaps_feedid_topicid_key;
+
+alter table tickersentiments
+    drop constraint if exists tickersentiments_feedid_sid_key;
+
+alter table feeds
+    drop constraint if exists feeds_sid_articleid_key;
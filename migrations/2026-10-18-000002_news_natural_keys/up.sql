@@ -0,0 +1,52 @@
+-- Natural keys for the news graph so that reloading an article upserts instead of duplicating.
+
+-- Point the children of duplicate feeds at the first feed of each (sid, articleid), then drop
+-- the duplicates.
+create temporary table feed_dups as
+select id, min(id) over (partition by sid, articleid) as keep_id
+from feeds;
+
+delete from feed_dups where id = keep_id;
+
+delete from tickersentiments t
+using feed_dups d
+where t.feedid = d.id
+  and exists (select 1 from tickersentiments k where k.feedid = d.keep_id and k.sid = t.sid);
+update tickersentiments t set feedid = d.keep_id from feed_dups d where t.feedid = d.id;
+
+delete from topicmaps t
+using feed_dups d
+where t.feedid = d.id
+  and exists (select 1 from topicmaps k where k.feedid = d.keep_id and k.topicid = t.topicid);
+update topicmaps t set feedid = d.keep_id from feed_dups d where t.feedid = d.id;
+
+delete from authormaps a
+using feed_dups d
+where a.feedid = d.id
+  and exists (select 1 from authormaps k where k.feedid = d.keep_id and k.authorid = a.authorid);
+update authormaps a set feedid = d.keep_id from feed_dups d where a.feedid = d.id;
+
+delete from feeds f using feed_dups d where f.id = d.id;
+
+drop table feed_dups;
+
+delete from tickersentiments t
+using tickersentiments k
+where t.feedid = k.feedid
+  and t.sid = k.sid
+  and t.id > k.id;
+
+delete from topicmaps t
+using topicmaps k
+where t.feedid = k.feedid
+  and t.topicid = k.topicid
+  and t.id > k.id;
+
+alter table feeds
+    add constraint feeds_sid_articleid_key unique (sid, articleid);
+
+alter table tickersentiments
+    add constraint tickersentiments_feedid_sid_key unique (feedid, sid);
+
+alter table topicmaps
+    add constraint topicmaps_feedid_topicid_key unique (feedid, topicid);
@@ -0,0 +1,7 @@
+drop index if exists articles_duplicate_of_idx;
+drop index if exists articles_ct_idx;
+drop index if exists articles_canonical_url_idx;
+
+alter table articles
+    drop column if exists duplicate_of,
+    drop column if exists canonical_url;
@@ -0,0 +1,7 @@
+alter table articles
+    add column canonical_url text,
+    add column duplicate_of  text references articles (hashid) on delete set null;
+
+create index articles_canonical_url_idx on articles (canonical_url);
+create index articles_ct_idx on articles (ct);
+create index articles_duplicate_of_idx on articles (duplicate_of);
@@ -0,0 +1,4 @@
+drop index if exists articles_search_idx;
+
+alter table articles
+    drop column if exists search;
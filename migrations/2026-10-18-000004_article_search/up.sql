@@ -0,0 +1,8 @@
+-- the column is maintained by postgres and only read through dbfunctions::news_search
+alter table articles
+    add column search tsvector generated always as (
+        setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(summary, '')), 'B')
+    ) stored;
+
+create index articles_search_idx on articles using gin (search);
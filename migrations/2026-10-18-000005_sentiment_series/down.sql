@@ -0,0 +1,6 @@
+drop table if exists sentimentseries;
+
+drop index if exists feeds_loaded_at_idx;
+
+alter table feeds
+    drop column if exists loaded_at;
@@ -0,0 +1,24 @@
+alter table feeds
+    add column loaded_at timestamptz not null default now();
+
+create index feeds_loaded_at_idx on feeds (loaded_at);
+
+-- relevance weighted ticker sentiment of a sid per day (US/Eastern) or hour,
+-- rebuilt by dbfunctions::sentiment_series::refresh_sentiment_series
+create table sentimentseries
+(
+    sid                bigint      not null references symbols (sid),
+    granularity        text        not null check (granularity in ('day', 'hour')),
+    bucket             timestamptz not null,
+    articles           integer     not null,
+    weighted_sentiment float8,
+    mean_relevance     float8      not null,
+    bullish            integer     not null,
+    somewhat_bullish   integer     not null,
+    neutral            integer     not null,
+    somewhat_bearish   integer     not null,
+    bearish            integer     not null,
+    sources            integer     not null,
+    refreshed_at       timestamptz not null,
+    primary key (sid, granularity, bucket)
+);
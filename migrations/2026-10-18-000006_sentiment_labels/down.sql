@@ -0,0 +1,12 @@
+alter table newsoverviews
+    drop column if exists definitionid;
+
+drop table if exists sentimentdefinitions;
+
+alter table tickersentiments
+    alter column sentimentlable type text using sentimentlable::text;
+
+alter table feeds
+    alter column sentlabel type text using sentlabel::text;
+
+drop type if exists sentiment_label;
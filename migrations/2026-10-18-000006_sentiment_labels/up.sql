@@ -0,0 +1,45 @@
+create type sentiment_label as enum (
+    'Bearish', 'Somewhat-Bearish', 'Neutral', 'Somewhat-Bullish', 'Bullish'
+);
+
+-- labels are matched ignoring case and separators; anything else is derived from the score
+-- with the published Alpha Vantage thresholds
+create function pg_temp.to_sentiment_label(label text, score float8) returns sentiment_label as
+$$
+select case lower(regexp_replace(label, '[-_ ]', '', 'g'))
+           when 'bearish' then 'Bearish'
+           when 'somewhatbearish' then 'Somewhat-Bearish'
+           when 'neutral' then 'Neutral'
+           when 'somewhatbullish' then 'Somewhat-Bullish'
+           when 'bullish' then 'Bullish'
+           else case
+                    when score <= -0.35 then 'Bearish'
+                    when score <= -0.15 then 'Somewhat-Bearish'
+                    when score < 0.15 then 'Neutral'
+                    when score < 0.35 then 'Somewhat-Bullish'
+                    else 'Bullish'
+               end
+       end::sentiment_label
+$$ language sql immutable;
+
+alter table feeds
+    alter column sentlabel type sentiment_label
+        using pg_temp.to_sentiment_label(sentlabel, osentiment);
+
+alter table tickersentiments
+    alter column sentimentlable type sentiment_label
+        using pg_temp.to_sentiment_label(sentimentlable, tsentiment);
+
+create table sentimentdefinitions
+(
+    id               serial primary key,
+    definition       text        not null unique,
+    bearish          float8      not null,
+    somewhat_bearish float8      not null,
+    somewhat_bullish float8      not null,
+    bullish          float8      not null,
+    created          timestamptz not null default now()
+);
+
+alter table newsoverviews
+    add column definitionid integer references sentimentdefinitions (id);
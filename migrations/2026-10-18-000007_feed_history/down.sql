@@ -0,0 +1,8 @@
+alter table feeds
+    drop column if exists last_seen,
+    drop column if exists first_seen;
+
+drop index if exists newsoverviews_sid_creation_idx;
+
+alter table newsoverviews
+    drop column if exists last_seen;
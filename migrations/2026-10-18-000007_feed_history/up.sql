@@ -0,0 +1,30 @@
+-- a newsoverviews row is a distinct feed snapshot of a sid: creation is when it was first
+-- returned, last_seen when it was last returned
+alter table newsoverviews
+    add column last_seen timestamptz;
+
+update newsoverviews
+set last_seen = creation;
+
+alter table newsoverviews
+    alter column last_seen set not null,
+    alter column last_seen set default now();
+
+create index newsoverviews_sid_creation_idx on newsoverviews (sid, creation);
+
+-- when an article was first and last returned in a feed of the sid
+alter table feeds
+    add column first_seen timestamptz,
+    add column last_seen  timestamptz;
+
+update feeds f
+set first_seen = o.creation,
+    last_seen  = greatest(o.creation, f.loaded_at)
+from newsoverviews o
+where o.id = f.newsoverviewid;
+
+alter table feeds
+    alter column first_seen set not null,
+    alter column first_seen set default now(),
+    alter column last_seen set not null,
+    alter column last_seen set default now();
@@ -0,0 +1 @@
+drop table if exists overview_history;
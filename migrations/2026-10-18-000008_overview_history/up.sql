@@ -0,0 +1,11 @@
+-- one row per fetched company overview; overviews/overviewexts only hold the latest fetch.
+-- the overview is kept as jsonb so the snapshot is independent of the width of those tables
+create table overview_history
+(
+    id         serial primary key,
+    sid        bigint      not null references symbols (sid),
+    fetched_at timestamptz not null default now(),
+    overview   jsonb       not null
+);
+
+create index overview_history_sid_fetched_at_idx on overview_history (sid, fetched_at);
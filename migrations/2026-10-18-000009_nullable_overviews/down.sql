@@ -0,0 +1,95 @@
+update overviews
+set name = coalesce(name, '__Error__'),
+    description = coalesce(description, '__Error__'),
+    cik = coalesce(cik, '__Error__'),
+    exch = coalesce(exch, '__Error__'),
+    curr = coalesce(curr, '__Error__'),
+    country = coalesce(country, '__Error__'),
+    sector = coalesce(sector, '__Error__'),
+    industry = coalesce(industry, '__Error__'),
+    address = coalesce(address, '__Error__'),
+    fiscalyearend = coalesce(fiscalyearend, '__Error__'),
+    latestquarter = coalesce(latestquarter, date '1900-01-01'),
+    marketcapitalization = coalesce(marketcapitalization, -999),
+    ebitda = coalesce(ebitda, -999),
+    peratio = coalesce(peratio, -9.99::real),
+    pegratio = coalesce(pegratio, -9.99::real),
+    bookvalue = coalesce(bookvalue, -9.99),
+    dividendpershare = coalesce(dividendpershare, -9.99::real),
+    dividendyield = coalesce(dividendyield, -9.99::real),
+    eps = coalesce(eps, -9.99::real);
+
+alter table overviews
+    alter column name set not null,
+    alter column description set not null,
+    alter column cik set not null,
+    alter column exch set not null,
+    alter column curr set not null,
+    alter column country set not null,
+    alter column sector set not null,
+    alter column industry set not null,
+    alter column address set not null,
+    alter column fiscalyearend set not null,
+    alter column latestquarter set not null,
+    alter column marketcapitalization set not null,
+    alter column ebitda set not null,
+    alter column peratio set not null,
+    alter column pegratio set not null,
+    alter column bookvalue set not null,
+    alter column dividendpershare set not null,
+    alter column dividendyield set not null,
+    alter column eps set not null;
+
+update overviewexts
+set revenuepersharettm = coalesce(revenuepersharettm, -9.99::real),
+    profitmargin = coalesce(profitmargin, -9.99::real),
+    operatingmarginttm = coalesce(operatingmarginttm, -9.99::real),
+    returnonassetsttm = coalesce(returnonassetsttm, -9.99::real),
+    returnonequityttm = coalesce(returnonequityttm, -9.99::real),
+    revenuettm = coalesce(revenuettm, -999),
+    grossprofitttm = coalesce(grossprofitttm, -999),
+    dilutedepsttm = coalesce(dilutedepsttm, -9.99::real),
+    quarterlyearningsgrowthyoy = coalesce(quarterlyearningsgrowthyoy, -9.99::real),
+    quarterlyrevenuegrowthyoy = coalesce(quarterlyrevenuegrowthyoy, -9.99::real),
+    analysttargetprice = coalesce(analysttargetprice, -9.99::real),
+    trailingpe = coalesce(trailingpe, -9.99::real),
+    forwardpe = coalesce(forwardpe, -9.99::real),
+    pricetosalesratiottm = coalesce(pricetosalesratiottm, -9.99::real),
+    pricetobookratio = coalesce(pricetobookratio, -9.99::real),
+    evtorevenue = coalesce(evtorevenue, -9.99::real),
+    evtoebitda = coalesce(evtoebitda, -9.99::real),
+    beta = coalesce(beta, -9.99),
+    annweekhigh = coalesce(annweekhigh, -9.99),
+    annweeklow = coalesce(annweeklow, -9.99),
+    fiftydaymovingaverage = coalesce(fiftydaymovingaverage, -9.99),
+    twohdaymovingaverage = coalesce(twohdaymovingaverage, -9.99),
+    sharesoutstanding = coalesce(sharesoutstanding, -9.99),
+    dividenddate = coalesce(dividenddate, date '1900-01-01'),
+    exdividenddate = coalesce(exdividenddate, date '1900-01-01');
+
+alter table overviewexts
+    alter column revenuepersharettm set not null,
+    alter column profitmargin set not null,
+    alter column operatingmarginttm set not null,
+    alter column returnonassetsttm set not null,
+    alter column returnonequityttm set not null,
+    alter column revenuettm set not null,
+    alter column grossprofitttm set not null,
+    alter column dilutedepsttm set not null,
+    alter column quarterlyearningsgrowthyoy set not null,
+    alter column quarterlyrevenuegrowthyoy set not null,
+    alter column analysttargetprice set not null,
+    alter column trailingpe set not null,
+    alter column forwardpe set not null,
+    alter column pricetosalesratiottm set not null,
+    alter column pricetobookratio set not null,
+    alter column evtorevenue set not null,
+    alter column evtoebitda set not null,
+    alter column beta set not null,
+    alter column annweekhigh set not null,
+    alter column annweeklow set not null,
+    alter column fiftydaymovingaverage set not null,
+    alter column twohdaymovingaverage set not null,
+    alter column sharesoutstanding set not null,
+    alter column dividenddate set not null,
+    alter column exdividenddate set not null;
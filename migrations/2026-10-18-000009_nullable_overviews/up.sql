@@ -0,0 +1,97 @@
+-- FullOverview used to store -9.99, -999, '__Error__' and 1900-01-01 for missing or unparseable
+-- fields and kept Alpha Vantage's 'None' and '-' text as is; those become null
+alter table overviews
+    alter column name drop not null,
+    alter column description drop not null,
+    alter column cik drop not null,
+    alter column exch drop not null,
+    alter column curr drop not null,
+    alter column country drop not null,
+    alter column sector drop not null,
+    alter column industry drop not null,
+    alter column address drop not null,
+    alter column fiscalyearend drop not null,
+    alter column latestquarter drop not null,
+    alter column marketcapitalization drop not null,
+    alter column ebitda drop not null,
+    alter column peratio drop not null,
+    alter column pegratio drop not null,
+    alter column bookvalue drop not null,
+    alter column dividendpershare drop not null,
+    alter column dividendyield drop not null,
+    alter column eps drop not null;
+
+update overviews
+set name = case when name in ('__Error__', 'None', '-', '') then null else name end,
+    description = case when description in ('__Error__', 'None', '-', '') then null else description end,
+    cik = case when cik in ('__Error__', 'None', '-', '') then null else cik end,
+    exch = case when exch in ('__Error__', 'None', '-', '') then null else exch end,
+    curr = case when curr in ('__Error__', 'None', '-', '') then null else curr end,
+    country = case when country in ('__Error__', 'None', '-', '') then null else country end,
+    sector = case when sector in ('__Error__', 'None', '-', '') then null else sector end,
+    industry = case when industry in ('__Error__', 'None', '-', '') then null else industry end,
+    address = case when address in ('__Error__', 'None', '-', '') then null else address end,
+    fiscalyearend = case when fiscalyearend in ('__Error__', 'None', '-', '') then null else fiscalyearend end,
+    latestquarter = nullif(latestquarter, date '1900-01-01'),
+    marketcapitalization = nullif(marketcapitalization, -999),
+    ebitda = nullif(ebitda, -999),
+    peratio = nullif(peratio, -9.99::real),
+    pegratio = nullif(pegratio, -9.99::real),
+    bookvalue = nullif(bookvalue, -9.99),
+    dividendpershare = nullif(dividendpershare, -9.99::real),
+    dividendyield = nullif(dividendyield, -9.99::real),
+    eps = nullif(eps, -9.99::real);
+
+alter table overviewexts
+    alter column revenuepersharettm drop not null,
+    alter column profitmargin drop not null,
+    alter column operatingmarginttm drop not null,
+    alter column returnonassetsttm drop not null,
+    alter column returnonequityttm drop not null,
+    alter column revenuettm drop not null,
+    alter column grossprofitttm drop not null,
+    alter column dilutedepsttm drop not null,
+    alter column quarterlyearningsgrowthyoy drop not null,
+    alter column quarterlyrevenuegrowthyoy drop not null,
+    alter column analysttargetprice drop not null,
+    alter column trailingpe drop not null,
+    alter column forwardpe drop not null,
+    alter column pricetosalesratiottm drop not null,
+    alter column pricetobookratio drop not null,
+    alter column evtorevenue drop not null,
+    alter column evtoebitda drop not null,
+    alter column beta drop not null,
+    alter column annweekhigh drop not null,
+    alter column annweeklow drop not null,
+    alter column fiftydaymovingaverage drop not null,
+    alter column twohdaymovingaverage drop not null,
+    alter column sharesoutstanding drop not null,
+    alter column dividenddate drop not null,
+    alter column exdividenddate drop not null;
+
+update overviewexts
+set revenuepersharettm = nullif(revenuepersharettm, -9.99::real),
+    profitmargin = nullif(profitmargin, -9.99::real),
+    operatingmarginttm = nullif(operatingmarginttm, -9.99::real),
+    returnonassetsttm = nullif(returnonassetsttm, -9.99::real),
+    returnonequityttm = nullif(returnonequityttm, -9.99::real),
+    revenuettm = nullif(revenuettm, -999),
+    grossprofitttm = nullif(grossprofitttm, -999),
+    dilutedepsttm = nullif(dilutedepsttm, -9.99::real),
+    quarterlyearningsgrowthyoy = nullif(quarterlyearningsgrowthyoy, -9.99::real),
+    quarterlyrevenuegrowthyoy = nullif(quarterlyrevenuegrowthyoy, -9.99::real),
+    analysttargetprice = nullif(analysttargetprice, -9.99::real),
+    trailingpe = nullif(trailingpe, -9.99::real),
+    forwardpe = nullif(forwardpe, -9.99::real),
+    pricetosalesratiottm = nullif(pricetosalesratiottm, -9.99::real),
+    pricetobookratio = nullif(pricetobookratio, -9.99::real),
+    evtorevenue = nullif(evtorevenue, -9.99::real),
+    evtoebitda = nullif(evtoebitda, -9.99::real),
+    beta = nullif(beta, -9.99),
+    annweekhigh = nullif(annweekhigh, -9.99),
+    annweeklow = nullif(annweeklow, -9.99),
+    fiftydaymovingaverage = nullif(fiftydaymovingaverage, -9.99),
+    twohdaymovingaverage = nullif(twohdaymovingaverage, -9.99),
+    sharesoutstanding = nullif(sharesoutstanding, -9.99),
+    dividenddate = nullif(dividenddate, date '1900-01-01'),
+    exdividenddate = nullif(exdividenddate, date '1900-01-01');
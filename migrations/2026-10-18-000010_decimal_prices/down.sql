@@ -0,0 +1,45 @@
+alter table intradayprices
+    alter column open type real,
+    alter column high type real,
+    alter column low type real,
+    alter column close type real;
+
+alter table summaryprices
+    alter column open type real,
+    alter column high type real,
+    alter column low type real,
+    alter column close type real;
+
+alter table topstats
+    alter column price type real,
+    alter column change_val type real,
+    alter column change_pct type real;
+
+alter table overviews
+    alter column peratio type real,
+    alter column pegratio type real,
+    alter column dividendpershare type real,
+    alter column dividendyield type real,
+    alter column eps type real,
+    alter column bookvalue type double precision;
+
+alter table overviewexts
+    alter column revenuepersharettm type real,
+    alter column profitmargin type real,
+    alter column operatingmarginttm type real,
+    alter column returnonassetsttm type real,
+    alter column returnonequityttm type real,
+    alter column dilutedepsttm type real,
+    alter column quarterlyearningsgrowthyoy type real,
+    alter column quarterlyrevenuegrowthyoy type real,
+    alter column analysttargetprice type real,
+    alter column trailingpe type real,
+    alter column forwardpe type real,
+    alter column pricetosalesratiottm type real,
+    alter column pricetobookratio type real,
+    alter column evtorevenue type real,
+    alter column evtoebitda type real,
+    alter column annweekhigh type double precision,
+    alter column annweeklow type double precision,
+    alter column fiftydaymovingaverage type double precision,
+    alter column twohdaymovingaverage type double precision;
@@ -0,0 +1,46 @@
+-- prices and monetary fundamentals are stored exactly; real kept about 7 significant digits
+alter table intradayprices
+    alter column open type numeric,
+    alter column high type numeric,
+    alter column low type numeric,
+    alter column close type numeric;
+
+alter table summaryprices
+    alter column open type numeric,
+    alter column high type numeric,
+    alter column low type numeric,
+    alter column close type numeric;
+
+alter table topstats
+    alter column price type numeric,
+    alter column change_val type numeric,
+    alter column change_pct type numeric;
+
+alter table overviews
+    alter column peratio type numeric,
+    alter column pegratio type numeric,
+    alter column dividendpershare type numeric,
+    alter column dividendyield type numeric,
+    alter column eps type numeric,
+    alter column bookvalue type numeric;
+
+alter table overviewexts
+    alter column revenuepersharettm type numeric,
+    alter column profitmargin type numeric,
+    alter column operatingmarginttm type numeric,
+    alter column returnonassetsttm type numeric,
+    alter column returnonequityttm type numeric,
+    alter column dilutedepsttm type numeric,
+    alter column quarterlyearningsgrowthyoy type numeric,
+    alter column quarterlyrevenuegrowthyoy type numeric,
+    alter column analysttargetprice type numeric,
+    alter column trailingpe type numeric,
+    alter column forwardpe type numeric,
+    alter column pricetosalesratiottm type numeric,
+    alter column pricetobookratio type numeric,
+    alter column evtorevenue type numeric,
+    alter column evtoebitda type numeric,
+    alter column annweekhigh type numeric,
+    alter column annweeklow type numeric,
+    alter column fiftydaymovingaverage type numeric,
+    alter column twohdaymovingaverage type numeric;
@@ -0,0 +1,14 @@
+-- volumes beyond the range of integer are clamped
+alter table overviewexts
+    alter column sharesoutstanding type double precision;
+
+alter table topstats
+    alter column volume type integer using least(volume, 2147483647)::integer;
+
+alter table summaryprices
+    drop column if exists fractional_volume,
+    alter column volume type integer using least(volume, 2147483647)::integer;
+
+alter table intradayprices
+    drop column if exists fractional_volume,
+    alter column volume type integer using least(volume, 2147483647)::integer;
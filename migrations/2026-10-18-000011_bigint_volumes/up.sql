@@ -0,0 +1,15 @@
+-- daily volumes of the most active names and crypto exceed 2^31. crypto volumes are fractional:
+-- volume holds them rounded and fractional_volume the exact value
+alter table intradayprices
+    alter column volume type bigint,
+    add column fractional_volume numeric;
+
+alter table summaryprices
+    alter column volume type bigint,
+    add column fractional_volume numeric;
+
+alter table topstats
+    alter column volume type bigint;
+
+alter table overviewexts
+    alter column sharesoutstanding type bigint using round(sharesoutstanding)::bigint;
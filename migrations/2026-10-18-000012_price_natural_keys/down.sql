@@ -0,0 +1,5 @@
+alter table summaryprices
+    drop constraint if exists summaryprices_sid_date_key;
+
+alter table intradayprices
+    drop constraint if exists intradayprices_sid_tstamp_key;
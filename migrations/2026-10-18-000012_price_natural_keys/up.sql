@@ -0,0 +1,21 @@
+-- a bar is identified by its sid and time. duplicates left by reloads are removed first, keeping
+-- the most recently inserted row (the same rule as the dedupe_prices binary)
+delete
+from intradayprices p
+    using intradayprices q
+where p.sid = q.sid
+  and p.tstamp = q.tstamp
+  and p.eventid < q.eventid;
+
+delete
+from summaryprices p
+    using summaryprices q
+where p.sid = q.sid
+  and p.date = q.date
+  and p.eventid < q.eventid;
+
+alter table intradayprices
+    add constraint intradayprices_sid_tstamp_key unique (sid, tstamp);
+
+alter table summaryprices
+    add constraint summaryprices_sid_date_key unique (sid, date);
@@ -0,0 +1,24 @@
+-- load state of the intraday and daily prices of a sid. a sid without a row has never been
+-- loaded and gets a full backfill; later loads are incremental
+create table priceloads
+(
+    sid          bigint      not null references symbols (sid),
+    kind         text        not null check (kind in ('intraday', 'summary')),
+    first_loaded timestamptz not null default now(),
+    last_loaded  timestamptz not null default now(),
+    -- the latest bar stored: last_tstamp for intraday prices, last_date for daily prices
+    last_tstamp  timestamptz,
+    last_date    date,
+    primary key (sid, kind)
+);
+
+-- symbols loaded before this table existed count as loaded
+insert into priceloads (sid, kind, last_tstamp)
+select sid, 'intraday', max(tstamp)
+from intradayprices
+group by sid;
+
+insert into priceloads (sid, kind, last_date)
+select sid, 'summary', max(date)
+from summaryprices
+group by sid;
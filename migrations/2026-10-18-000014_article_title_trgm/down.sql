@@ -0,0 +1,3 @@
+drop index if exists articles_title_trgm_idx;
+
+drop extension if exists pg_trgm;
@@ -0,0 +1,4 @@
+-- trigram index on titles, narrows the near-duplicate candidates of a new article
+create extension if not exists pg_trgm;
+
+create index articles_title_trgm_idx on articles using gin (title gin_trgm_ops);
@@ -0,0 +1,13 @@
+-- one row per refresh of sentimentseries; loaded_through is the newest feeds.loaded_at
+-- it processed, the high-water mark of the next refresh
+create table sentimentrefreshes
+(
+    id             serial primary key,
+    refreshed_at   timestamptz not null default now(),
+    loaded_through timestamptz not null
+);
+
+insert into sentimentrefreshes (refreshed_at, loaded_through)
+select max(refreshed_at), max(refreshed_at)
+from sentimentseries
+having max(refreshed_at) is not null;
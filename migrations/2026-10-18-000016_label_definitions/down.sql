@@ -0,0 +1,5 @@
+alter table tickersentiments
+    drop column if exists definitionid;
+
+alter table feeds
+    drop column if exists definitionid;
@@ -0,0 +1,7 @@
+-- the sentiment definition whose thresholds derived a stored label, null while the label is
+-- the one Alpha Vantage supplied
+alter table feeds
+    add column definitionid integer references sentimentdefinitions (id);
+
+alter table tickersentiments
+    add column definitionid integer references sentimentdefinitions (id);
@@ -0,0 +1,4 @@
+-- unique (date) from the first topstats table survived rem_uniq_from_topstats, which added
+-- topstats_pk instead of dropping it, and allows only one top stat per load
+alter table topstats
+    drop constraint if exists topstats_date_key;
@@ -0,0 +1,20 @@
+drop index if exists newsoverviews_sid_last_seen_idx;
+create index newsoverviews_sid_creation_idx on newsoverviews (sid, creation);
+
+-- file the fanned out rows under the latest snapshot of their sid, and drop those of sids that
+-- never had their own feed loaded
+update feeds f
+set newsoverviewid = (select o.id
+                      from newsoverviews o
+                      where o.sid = f.sid
+                      order by o.last_seen desc, o.id desc
+                      limit 1)
+where f.newsoverviewid is null;
+
+delete from authormaps where feedid in (select id from feeds where newsoverviewid is null);
+delete from tickersentiments where feedid in (select id from feeds where newsoverviewid is null);
+delete from topicmaps where feedid in (select id from feeds where newsoverviewid is null);
+delete from feeds where newsoverviewid is null;
+
+alter table feeds
+    alter column newsoverviewid set not null;
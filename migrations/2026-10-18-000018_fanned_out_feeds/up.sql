@@ -0,0 +1,8 @@
+-- newsoverviews holds the snapshots of a sid's own feed only. A feed row stored from the feed of
+-- another ticker or of a topic, that mentioned the sid, has no snapshot
+alter table feeds
+    alter column newsoverviewid drop not null;
+
+-- the latest snapshot of a sid is the one seen last
+drop index if exists newsoverviews_sid_creation_idx;
+create index newsoverviews_sid_last_seen_idx on newsoverviews (sid, last_seen);
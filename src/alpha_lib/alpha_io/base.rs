@@ -31,8 +31,10 @@ extern crate chrono_tz;
 
 use std::{collections::HashMap, env::VarError, thread, time};
 
-use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime};
-use diesel::PgConnection;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use diesel::{Connection, PgConnection};
 use serde_json::Value;
 use thiserror::Error;
 
@@ -60,6 +62,8 @@ pub enum Error {
   MissingHeader(String),
   #[error("Max exceeded : {0}")]
   MaxExceeded(String),
+  #[error("Invalid local time: {0}")]
+  LocalTime(String),
   #[error("Unexpected error: {0}")]
   UnEx(String),
 }
@@ -67,19 +71,24 @@ pub enum Error {
 use crate::{
   alpha_lib::core::{
     alpha_data_types::{
-      AlphaSymbol, Convert, FullOverview, RawDailyPrice, RawIntraDayPrice, Root, TopType,
+      AlphaSymbol, Convert, FullOverview, RawDailyPrice, RawIntraDayPrice, Root, TopType, Volume,
     },
     alpha_funcs::{normalize_alpha_region, top_constants},
+    market_calendar::{
+      parse_symbol_timezone, symbol_timezone, to_utc, ALPHA_VANTAGE_TZ, CRYPTO_TZ,
+    },
+    market_status::{MarketSchedule, MarketStatusRoot},
     news_type::NewsRoot,
   },
   create_url,
   db_models::IntraDayPrice,
   dbfunctions::{
-    base::establish_connection_or_exit,
     overview::create_overview,
-    price::{create_intra_day, get_intr_day_max_date, get_summary_max_date, insert_open_close},
+    price::{upsert_intraday_prices, upsert_summary_prices, PriceTable},
+    price_loads::{get_price_load, record_intraday_load, record_summary_load, LoadMode},
+    process::get_last_run,
     sid::{get_next_sid, get_sid},
-    symbols::{create_symbol, get_symbols_and_sids_for},
+    symbols::{create_symbol, get_region_and_timezone, get_symbols_and_sids_for},
     tops::insert_top_stat,
   },
   security_types::sec_types::SecurityType,
@@ -118,7 +127,8 @@ pub fn get_api_key() -> Result<String, VarError> {
 /// ```ignore
 /// use alpha_vantage_rust::alpha_lib::alpha_io_funcs::process_symbols;
 /// let symbols = vec![vec!["AAPL".to_string(),  "GOOG".to_string()], vec!["TSLA".to_string()]];
-/// let result = process_symbols(symbols);
+/// let conn = &mut establish_connection_or_exit();
+/// let result = process_symbols(conn, symbols, false);
 ///
 /// match result {
 ///     Ok(_) => println!("Operation completed successfully."),
@@ -128,14 +138,17 @@ pub fn get_api_key() -> Result<String, VarError> {
 ///
 ///
 /// TODO:  Refactor as this is a bit of a mess
-pub fn process_symbols(sec_vec: Vec<Vec<String>>, load_missed: bool) -> Result<(), Error> {
+pub fn process_symbols(
+  conn: &mut PgConnection,
+  sec_vec: Vec<Vec<String>>,
+  load_missed: bool,
+) -> Result<(), Error> {
   let api_key = get_api_key()?;
 
   let mut type_map: HashMap<SecurityType, i32> = HashMap::new();
   let mut symbol_map: HashMap<String, i32> = HashMap::new();
   let mut err_ct = 0;
 
-  let conn = &mut establish_connection_or_exit();
   let mut dur_time: DateTime<Local>;
   let mut resp_time: DateTime<Local>;
   let min_time = Duration::milliseconds(350); //We cant make MIN_TIME a constant because it is not a primitive type
@@ -275,7 +288,10 @@ pub fn get_overview(connection: &mut PgConnection, s_id: i64, symb: String) -> R
   }
   let json = serde_json::from_str::<Value>(&text)?;
   let ov = FullOverview::new(s_id, json);
-  if let Some(ov) = ov {
+  if let Some((ov, field_errors)) = ov {
+    for err in field_errors {
+      println!("Overview for symbol {}: {}", symb, err);
+    }
     println!("Overview: {:?}", ov);
     create_overview(connection, ov)?;
   } else {
@@ -301,6 +317,38 @@ fn get_top_data(url: &str) -> Result<Root, Error> {
   Ok(text)
 }
 
+/// Retrieves the current open/closed status of the global markets from the MARKET_STATUS
+/// endpoint.
+pub fn get_market_status() -> Result<MarketStatusRoot, Error> {
+  let api_key = get_api_key()?;
+  let url = create_url!(FuncType::MarketStatus, "", api_key);
+  let response = reqwest::blocking::get(url)?;
+  let root = response.json::<MarketStatusRoot>()?;
+  Ok(root)
+}
+
+/// Builds the `MarketSchedule` for a process from the last successful run of `proc_id` and the
+/// current MARKET_STATUS.
+///
+/// A failed MARKET_STATUS request is not fatal: the schedule then relies on the local holiday
+/// calendar alone.
+pub fn get_market_schedule(conn: &mut PgConnection, proc_id: i32) -> Result<MarketSchedule, Error> {
+  let last_run = get_last_run(conn, proc_id)?
+    .and_then(|t| t.and_local_timezone(Local).earliest())
+    .map(|t| t.with_timezone(&Utc));
+  let status = match get_market_status() {
+    Ok(status) => Some(status),
+    Err(err) => {
+      eprintln!(
+        "Cannot retrieve market status, using holiday calendar only: {}",
+        err
+      );
+      None
+    }
+  };
+  Ok(MarketSchedule::new(status.as_ref(), last_run, Utc::now()))
+}
+
 pub fn get_news_root(url: &str) -> Result<NewsRoot, Error> {
   let response = reqwest::blocking::get(url)?;
   let text = response.json::<NewsRoot>()?;
@@ -315,40 +363,47 @@ fn parse_intraday_from_csv(text: &str) -> Result<Vec<RawIntraDayPrice>, Error> {
     .map_err(|e| e.into())
 }
 
+/// Parses an exchange local timestamp ("YYYY-MM-DD HH:MM:SS") and converts it to UTC.
+fn parse_local_time(inp: &str, tz: Tz) -> Result<DateTime<Utc>, Error> {
+  let local = NaiveDateTime::parse_from_str(inp, "%Y-%m-%d %H:%M:%S")?;
+  to_utc(local, tz).ok_or_else(|| Error::LocalTime(format!("{} does not exist in {}", inp, tz)))
+}
+
 fn persist_ticks(
   connection: &mut PgConnection,
   s_id: i64,
   symb: &String,
   ticks: Vec<RawIntraDayPrice>,
+  tz: Tz,
 ) -> Result<(), Error> {
-  let last_date = get_intr_day_max_date(connection, s_id)?;
-
-  let mut _skipped = 0;
-
-  let mut _processed = 0;
-
+  let mut bars = Vec::with_capacity(ticks.len());
   for tick in ticks {
-    let tmp_tick = IntraDayPrice {
+    bars.push(IntraDayPrice {
       eventid: 0,
-      tstamp: NaiveDateTime::parse_from_str(&tick.timestamp, "%Y-%m-%d %H:%M:%S")?,
+      tstamp: parse_local_time(&tick.timestamp, tz)?,
       sid: s_id,
       symbol: symb.clone(),
       open: tick.open,
       high: tick.high,
       low: tick.low,
       close: tick.close,
-      volume: tick.volume,
-    };
-    if tmp_tick.tstamp > last_date {
-      let _ = create_intra_day(connection, tmp_tick);
-      _processed += 1;
-    } else {
-      _skipped += 1;
-    }
+      volume: tick.volume.whole,
+      fractional_volume: tick.volume.fractional,
+    });
   }
+  // an empty response does not count as a load, the next one backfills again
+  let Some(last_tstamp) = bars.iter().map(|bar| bar.tstamp).max() else {
+    return Ok(());
+  };
+  connection.transaction(|conn| {
+    upsert_intraday_prices(conn, s_id, &bars)?;
+    record_intraday_load(conn, s_id, last_tstamp)
+  })?;
   Ok(())
 }
 
+/// Loads the intraday bars of a symbol. The first load requests the full history the endpoint
+/// offers, later loads only the latest bars. The load is recorded in `priceloads`.
 pub fn load_intraday(
   conn: &mut PgConnection,
   symb: &String,
@@ -357,9 +412,24 @@ pub fn load_intraday(
 ) -> Result<(), Error> {
   const HEADER: &str = "timestamp,open,high,low,close,volume";
   let api_key = get_api_key()?;
-  let url = match sectype {
-    SecurityType::Crypto => create_url!(FuncType::CryptoIntraDay, symb, api_key),
-    SecurityType::Equity => create_url!(FuncType::TsIntra, symb, api_key),
+  let mode = LoadMode::for_state(get_price_load(conn, s_id, PriceTable::Intraday)?.as_ref());
+  let (url, tz) = match (sectype, mode) {
+    (SecurityType::Crypto, LoadMode::Backfill) => (
+      create_url!(FuncType::CryptoIntraDayFull, symb, api_key),
+      CRYPTO_TZ,
+    ),
+    (SecurityType::Crypto, LoadMode::Incremental) => (
+      create_url!(FuncType::CryptoIntraDay, symb, api_key),
+      CRYPTO_TZ,
+    ),
+    (SecurityType::Equity, _) => {
+      let (region, timezone) = get_region_and_timezone(conn, s_id)?;
+      let url = match mode {
+        LoadMode::Backfill => create_url!(FuncType::TsIntraFull, symb, api_key),
+        LoadMode::Incremental => create_url!(FuncType::TsIntra, symb, api_key),
+      };
+      (url, symbol_timezone(&region, &timezone))
+    }
     _ => panic!("Unknown security type"),
   };
 
@@ -372,7 +442,7 @@ pub fn load_intraday(
   };
 
   let ticks = parse_intraday_from_csv(&text)?;
-  persist_ticks(conn, s_id, &symb, ticks)?;
+  persist_ticks(conn, s_id, &symb, ticks, tz)?;
 
   Ok(())
 }
@@ -389,23 +459,27 @@ fn gen_new_summary_price(json_inp: (&String, &Value), sym: String) -> Option<Raw
   let open = json_inp.1["1. open"]
     .as_str()
     .unwrap()
-    .parse::<f32>()
+    .parse::<BigDecimal>()
     .ok()?;
   let high = json_inp.1["2. high"]
     .as_str()
     .unwrap()
-    .parse::<f32>()
+    .parse::<BigDecimal>()
+    .ok()?;
+  let low = json_inp.1["3. low"]
+    .as_str()
+    .unwrap()
+    .parse::<BigDecimal>()
     .ok()?;
-  let low = json_inp.1["3. low"].as_str().unwrap().parse::<f32>().ok()?;
   let close = json_inp.1["4. close"]
     .as_str()
     .unwrap()
-    .parse::<f32>()
+    .parse::<BigDecimal>()
     .ok()?;
   let volume = json_inp.1["5. volume"]
     .as_str()
     .unwrap()
-    .parse::<i32>()
+    .parse::<Volume>()
     .ok()?;
 
   Some(RawDailyPrice {
@@ -420,9 +494,9 @@ fn gen_new_summary_price(json_inp: (&String, &Value), sym: String) -> Option<Raw
 }
 /// Loads and updates the daily stock price summaries for a given symbol.
 ///
-/// This function retrieves the daily stock prices from an external API and updates
-/// the database with the new information that has not yet been recorded up to the
-/// latest date known in the database for the given stock symbol and source ID.
+/// This function retrieves the daily stock prices from an external API and upserts them, so new
+/// days are added and days the vendor revised are updated. The first load of a symbol requests
+/// the full history, later loads only the latest 100 days. The load is recorded in `priceloads`.
 ///
 /// # Parameters
 /// - `conn`: A mutable reference to a PostgreSQL connection to perform database operations.
@@ -437,7 +511,7 @@ fn gen_new_summary_price(json_inp: (&String, &Value), sym: String) -> Option<Raw
 /// # Errors
 /// - Returns an error if the API key retrieval fails.
 /// - Returns an error if there is a failure in fetching or parsing the API data.
-/// - Database related errors are propagated if any insert operation fails.
+/// - Database related errors are propagated if the upsert fails.
 ///
 /// # Example
 /// ```ignore
@@ -457,11 +531,12 @@ fn gen_new_summary_price(json_inp: (&String, &Value), sym: String) -> Option<Raw
 /// - The function checks if the returned data from the API contains a specific header ("Meta
 ///   Data"). If this header is not present, it assumes that there was an error with the provided
 ///   symbol (such as missing price data) and will not perform any database updates for this symbol.
-/// - It logs the latest date for which data is available in the database and only inserts new
-///   records for dates that are after this last known date.
 pub fn load_summary(conn: &mut PgConnection, symb: &str, s_id: i64) -> Result<(), Error> {
   let api_key = get_api_key()?;
-  let url = create_url!(FuncType::TsDaily, symb, api_key);
+  let url = match LoadMode::for_state(get_price_load(conn, s_id, PriceTable::Summary)?.as_ref()) {
+    LoadMode::Backfill => create_url!(FuncType::TsDailyFull, symb, api_key),
+    LoadMode::Incremental => create_url!(FuncType::TsDaily, symb, api_key),
+  };
   let text = get_api_data(&url)?;
   const HEADER: &str = "Meta Data";
   if !text.contains(HEADER) {
@@ -472,18 +547,14 @@ pub fn load_summary(conn: &mut PgConnection, symb: &str, s_id: i64) -> Result<()
   };
 
   let daily_prices = get_open_close(&text, &symb.to_string())?;
-  let last_date = get_summary_max_date(conn, s_id)?;
-  //todo: improve logging here
-
-  // println!("last date for sid{} is {:?}", s_id, last_date);
-  for oc in daily_prices {
-    //todo: improve logging here
-
-    // print!("{:?}", oc.date);
-    if oc.date > last_date {
-      insert_open_close(conn, &symb.to_string(), s_id, oc)?;
-    }
-  }
+  // an empty response does not count as a load, the next one backfills again
+  let Some(last_date) = daily_prices.iter().map(|oc| oc.date).max() else {
+    return Ok(());
+  };
+  conn.transaction(|conn| {
+    upsert_summary_prices(conn, symb, s_id, &daily_prices)?;
+    record_summary_load(conn, s_id, last_date)
+  })?;
 
   Ok(())
 }
@@ -541,16 +612,15 @@ fn get_open_close(inp: &str, symb: &String) -> Result<Vec<RawDailyPrice>, Error>
   Ok(daily_prices)
 }
 
-pub fn process_digital_symbols(sed_vec: Vec<String>) -> Result<(), Error> {
+pub fn process_digital_symbols(conn: &mut PgConnection, sed_vec: Vec<String>) -> Result<(), Error> {
   let sec_type = "Crypto";
   let region = "USA";
   let currency = "USD";
-  let timezone = "UTC-04";
+  let timezone = "UTC";
   let marketopen = "00:00";
   let marketclose = "23:59";
 
   let mut symbol_map: HashMap<String, i64> = HashMap::new();
-  let conn = &mut establish_connection_or_exit();
 
   // assuming there are no digital currencies
   let mut base_sid = 1;
@@ -675,7 +745,7 @@ fn process_data_for_type(
   conn: &mut PgConnection,
   data: &[impl Convert],
   top_type: TopType,
-  last_update: NaiveDateTime,
+  last_update: DateTime<Utc>,
 ) -> Result<(), Error> {
   for item in data {
     let tt = item.make_top_stat()?;
@@ -689,45 +759,31 @@ fn process_data_for_type(
   Ok(())
 }
 
-/// Parses a string to extract a date and time, ignoring the timezone.
-///
-/// This function takes a string input that contains a date, time, and timezone,
-/// parses it to extract the datetime component, and returns a `NaiveDateTime` object.
-/// The timezone part of the string is ignored during parsing.
+/// Parses the `last_updated` field of TOP_GAINERS_LOSERS and converts it to UTC.
 ///
-/// # Parameters
-/// - `inp`: A string in the format "YYYY-MM-DD HH:MM:SS TZ" where TZ is the timezone.
-///
-/// # Returns
-/// - `Ok(NaiveDateTime)` if the datetime string is successfully parsed.
-/// - `Err(e)` where `e` is a boxed error if parsing fails.
+/// The input is in the format "YYYY-MM-DD HH:MM:SS TZ", where TZ is an IANA zone name such as
+/// "US/Eastern". A missing zone is taken to be the Alpha Vantage default of US/Eastern.
 ///
 /// # Errors
-/// - The function will return an error if the input string does not contain exactly two parts
-///   separated by a space, or if the datetime part of the string is not in the expected format.
+/// - The function will return an error if the datetime part of the string is not in the expected
+///   format, if the zone is not recognized, or if the local time does not exist in that zone.
 ///
 /// # Example
 /// ```ignore
-/// use alpha_vantage_rust::time_functions::get_time_stamp;
-/// use chrono::NaiveDateTime;
-///
-/// let input = "2023-04-01 15:30:00 UTC";
-/// match get_time_stamp(input.to_string()) {
-///     Ok(datetime) => println!("Parsed datetime: {}", datetime),
-///     Err(e) => eprintln!("Error parsing datetime: {}", e),
-/// }
+/// let utc = get_time_stamp("2023-10-03 16:15:59 US/Eastern".to_string())?;
+/// assert_eq!(utc.to_string(), "2023-10-03 20:15:59 UTC");
 /// ```
-///
-/// # Remarks
-/// - This function assumes that the input string will always follow the format with a space
-///   separating the datetime and timezone components. It uses `rsplitn` with a limit of 2 to split
-///   the string from the end, ensuring that the last part (timezone) is separated first.
-fn get_time_stamp(inp: String) -> Result<NaiveDateTime, Error> {
-  let mut parts = inp.rsplitn(2, ' ');
-  let _tz = parts.next().unwrap();
-  let tm = parts.next().unwrap();
-  let naive_dt = NaiveDateTime::parse_from_str(tm, "%Y-%m-%d %H:%M:%S")?;
-  Ok(naive_dt)
+fn get_time_stamp(inp: String) -> Result<DateTime<Utc>, Error> {
+  let inp = inp.trim();
+  let (tm, tz) = match inp.rsplit_once(' ') {
+    Some((tm, zone)) if !zone.contains(':') => {
+      let tz = parse_symbol_timezone(zone)
+        .ok_or_else(|| Error::LocalTime(format!("Unknown time zone {}", zone)))?;
+      (tm, tz)
+    }
+    _ => (inp, ALPHA_VANTAGE_TZ),
+  };
+  parse_local_time(tm, tz)
 }
 
 #[cfg(test)]
@@ -740,4 +796,18 @@ mod test {
 
     assert!(get_time_stamp(inp.to_string()).is_ok());
   }
+
+  #[test]
+  fn t_002() {
+    let utc = get_time_stamp("2023-10-03 16:15:59 US/Eastern".to_string()).unwrap();
+    assert_eq!(utc.to_string(), "2023-10-03 20:15:59 UTC");
+
+    let utc = get_time_stamp("2023-12-04 16:15:59 US/Eastern".to_string()).unwrap();
+    assert_eq!(utc.to_string(), "2023-12-04 21:15:59 UTC");
+
+    let utc = get_time_stamp("2023-12-04 16:15:59".to_string()).unwrap();
+    assert_eq!(utc.to_string(), "2023-12-04 21:15:59 UTC");
+
+    assert!(get_time_stamp("2023-12-04 16:15:59 Mars/Olympus".to_string()).is_err());
+  }
 }
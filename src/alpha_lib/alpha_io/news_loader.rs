@@ -29,47 +29,198 @@
 
 extern crate chrono;
 
-use std::{collections::HashMap, error::Error, fs::File, io::BufWriter};
+use std::{
+  collections::{BTreeMap, HashMap},
+  error::Error,
+  fs::File,
+  io::BufWriter,
+};
 
-use diesel::PgConnection;
+use diesel::{Connection, PgConnection};
 
 use crate::{
   alpha_lib::{
     alpha_io::{
       base::{get_api_key, get_news_root},
-      news_root::insert_news_root,
+      news_root::{feed_hash, get_latest_news_root, insert_news_root, touch_news_root},
+    },
+    core::{
+      alpha_funcs::normalize_author_name,
+      news_identity::canonical_url,
+      news_query::{parse_news_ticker, NewsQuery},
+      news_type::{NewsRoot, RawFeed, TickerSentiment, Topic},
+      sentiment_label::{SentimentLabel, SentimentThresholds},
     },
-    core::news_type::{NewsRoot, RawFeed, TickerSentiment, Topic},
     misc_functions::log_missed_symbol,
   },
-  create_url,
   dbfunctions::{
-    articles::insert_article, author::insert_author, author_map::insert_author_map,
-    feed::insert_feed, sources::insert_source, ticker_sentiments::ins_ticker_sentiment,
-    topic_maps::ins_topic_map, topic_refs::insert_topic,
+    articles::{get_latest_article_time, insert_article},
+    author::{find_author_by_name, insert_author},
+    author_map::insert_author_map,
+    feed::{insert_feed, mark_articles_seen},
+    sentiment_labels::get_or_insert_sentiment_definition,
+    sources::insert_source,
+    symbols::{get_or_create_currency, get_sid_of_type},
+    ticker_sentiments::ins_ticker_sentiment,
+    topic_maps::ins_topic_map,
+    topic_refs::insert_topic,
   },
+  security_types::sec_types::SecurityType,
 };
 
+const CRYPTO: &str = "Crypto";
+const NO_AUTHOR: &str = "_None_";
+
 #[derive(Debug, Default)]
 pub struct Params {
   pub topics: HashMap<String, i32>,
   pub authors: HashMap<String, i32>,
   pub sources: HashMap<String, i32>,
   pub names_to_sid: HashMap<String, i64>,
+  /// Filters applied to every request. `tickers` and `topics` are set per request.
+  pub query: NewsQuery,
+  /// Start each ticker at the newest article already stored for its sid.
+  pub incremental: bool,
+  /// Thresholds of the sentiment definition of the feed being stored, used to label scores whose
+  /// label is missing or unknown.
+  pub thresholds: SentimentThresholds,
 }
 
+/// Loads the news for a single ticker and fans each article out to every known ticker it
+/// mentions via its `ticker_sentiment`.
+///
+/// Each sid gets its own `feeds` row and only its own `tickersentiments` row, while the article
+/// itself is stored once. Tickers are requested one at a time: Alpha Vantage returns only the
+/// articles that mention *all* of the tickers in a request, so batching them would drop news.
+///
+/// In incremental mode `time_from` starts at the newest article stored for the sid. Articles
+/// already filed under a mentioned sid are skipped by `process_news`.
 pub fn load_news(
   conn: &mut PgConnection,
   s_id: &i64,
-  tkr: &String,
+  tkr: &str,
   params: &mut Params,
   symbol_log: &mut BufWriter<File>,
 ) -> Result<(), Box<dyn Error>> {
+  let latest = match params.incremental {
+    true => get_latest_article_time(conn, *s_id)?,
+    false => None,
+  };
+
+  let query = NewsQuery {
+    tickers: vec![tkr.to_string()],
+    topics: Vec::new(),
+    time_from: params.query.time_from.max(latest),
+    ..params.query.clone()
+  };
+
   let api_key = get_api_key()?;
-  let url = create_url!(FuncType::NewsQuery, tkr, api_key);
-  let root = get_news_root(&url)?;
+  let root = get_news_root(&query.url(&api_key))?;
+  fan_out(conn, root, Some(*s_id), params, symbol_log)
+}
+
+/// Loads the news for a topic and files each article under every known ticker it mentions.
+///
+/// # Arguments
+///
+/// * `topic` - A value of the `topics=` parameter, see `NEWS_TOPICS`.
+pub fn load_topic_news(
+  conn: &mut PgConnection,
+  topic: &str,
+  params: &mut Params,
+  symbol_log: &mut BufWriter<File>,
+) -> Result<(), Box<dyn Error>> {
+  let query = NewsQuery {
+    tickers: Vec::new(),
+    topics: vec![topic.to_string()],
+    ..params.query.clone()
+  };
+
+  let api_key = get_api_key()?;
+  let root = get_news_root(&query.url(&api_key))?;
+  fan_out(conn, root, None, params, symbol_log)
+}
+
+/// Files the articles of a feed under every known ticker they mention. Only `requested`, the sid
+/// whose own feed this is, gets the feed recorded as a snapshot and compared with its last one;
+/// the other sids only get their feed rows.
+fn fan_out(
+  conn: &mut PgConnection,
+  root: NewsRoot,
+  requested: Option<i64>,
+  params: &mut Params,
+  symbol_log: &mut BufWriter<File>,
+) -> Result<(), Box<dyn Error>> {
+  resolve_prefixed_tickers(conn, &root.feed, params)?;
+  params.thresholds =
+    SentimentThresholds::parse_definition(&root.sentiment_score_definition).unwrap_or_default();
+  for (s_id, (tkr, feed)) in group_feed_by_sid(&root.feed, &params.names_to_sid) {
+    let status = match requested == Some(s_id) {
+      true => {
+        let ticker_root = NewsRoot {
+          items: feed.len().to_string(),
+          sentiment_score_definition: root.sentiment_score_definition.clone(),
+          relevance_score_definition: root.relevance_score_definition.clone(),
+          feed,
+        };
+        process_news(conn, &s_id, &tkr, ticker_root, params, symbol_log)
+      }
+      false => process_feed(conn, &s_id, &tkr, feed, None, params, symbol_log),
+    };
+    if let Err(_err) = status {
+      // todo: Improve logging
+      // println!("Cannot process news for {}: {}", tkr, err);
+    }
+  }
+  Ok(())
+}
 
-  process_news(conn, s_id, tkr, root, params, symbol_log)
+/// Adds the "CRYPTO:" and "FOREX:" tickers of a feed to `names_to_sid`.
+///
+/// Digital currencies resolve to the Crypto symbols created by `process_digital_symbols`.
+/// Currencies are created as FX symbols the first time they are seen.
+fn resolve_prefixed_tickers(
+  conn: &mut PgConnection,
+  feed: &[RawFeed],
+  params: &mut Params,
+) -> Result<(), Box<dyn Error>> {
+  for sent in feed.iter().flat_map(|article| &article.ticker_sentiment) {
+    if params.names_to_sid.contains_key(&sent.ticker) {
+      continue;
+    }
+    let s_id = match parse_news_ticker(&sent.ticker) {
+      (Some(SecurityType::Crypto), symbol) => get_sid_of_type(conn, symbol, CRYPTO)?,
+      (Some(SecurityType::FX), code) => Some(get_or_create_currency(conn, code)?),
+      _ => None,
+    };
+    if let Some(s_id) = s_id {
+      params.names_to_sid.insert(sent.ticker.clone(), s_id);
+    }
+  }
+  Ok(())
+}
+
+/// Groups the articles of a feed by the sids of the known tickers they mention.
+///
+/// An article mentioning several known tickers appears once in the group of each of them.
+fn group_feed_by_sid(
+  feed: &[RawFeed],
+  names_to_sid: &HashMap<String, i64>,
+) -> BTreeMap<i64, (String, Vec<RawFeed>)> {
+  let mut groups: BTreeMap<i64, (String, Vec<RawFeed>)> = BTreeMap::new();
+  for article in feed {
+    for sent in &article.ticker_sentiment {
+      if let Some(&s_id) = names_to_sid.get(&sent.ticker) {
+        let (_, articles) = groups
+          .entry(s_id)
+          .or_insert_with(|| (sent.ticker.clone(), Vec::new()));
+        if articles.last().is_none_or(|last| last.url != article.url) {
+          articles.push(article.clone());
+        }
+      }
+    }
+  }
+  groups
 }
 
 pub fn process_news(
@@ -87,30 +238,72 @@ pub fn process_news(
     return Ok(());
   }
 
-  let res = insert_news_root(conn, *s_id, item_count, root.feed.clone());
-  if let Ok(overview) = res {
-    process_feed(conn, s_id, tkr, root.feed, overview.id, params, symbol_log)?;
-  } else if let Err(_e) = res {
-    //todo: "Improve logging here!"
-    // println!("Cannot insert news root for {}: err {} ", tkr, e);
-  }
-  Ok(())
+  params.thresholds =
+    SentimentThresholds::parse_definition(&root.sentiment_score_definition).unwrap_or_default();
+  let hash = feed_hash(&root.feed);
+  let overview = match get_latest_news_root(conn, *s_id)? {
+    Some(latest) if latest.hashid == hash => {
+      touch_news_root(conn, latest.id)?;
+      latest
+    }
+    _ => {
+      let definition_id = match root.sentiment_score_definition.trim() {
+        "" => None,
+        definition => Some(get_or_insert_sentiment_definition(
+          conn,
+          definition,
+          &params.thresholds,
+        )?),
+      };
+      insert_news_root(conn, *s_id, item_count, definition_id, &hash)?
+    }
+  };
+  process_feed(
+    conn,
+    s_id,
+    tkr,
+    root.feed,
+    Some(overview.id),
+    params,
+    symbol_log,
+  )
 }
 
+/// Files the articles of a feed under a sid. `overview_id` is the snapshot of the sid's own feed,
+/// none when the articles come from the feed of another ticker or a topic.
 fn process_feed(
   conn: &mut PgConnection,
   s_id: &i64,
   tkr: &String,
   feed: Vec<RawFeed>,
-  overview_id: i32,
+  overview_id: Option<i32>,
   params: &mut Params,
   symbol_log: &mut BufWriter<File>,
 ) -> Result<(), Box<dyn Error>> {
-  for article in feed {
-    process_article(conn, &s_id, &tkr, article, overview_id, params, symbol_log)?;
+  // Articles already filed under the sid only get their last seen time moved on. An unchanged
+  // feed therefore costs two queries, while an interrupted load is completed by running it again.
+  let urls: Vec<String> = feed.iter().map(|a| canonical_url(&a.url)).collect();
+  let seen = mark_articles_seen(conn, *s_id, &urls)?;
+  let new_articles: Vec<RawFeed> = feed
+    .into_iter()
+    .zip(urls)
+    .filter(|(_, url)| !seen.contains(url))
+    .map(|(article, _)| article)
+    .collect();
+
+  let mut failed = 0;
+  for article in new_articles {
+    if let Err(_err) = process_article(conn, s_id, tkr, article, overview_id, params, symbol_log) {
+      // todo: Improve logging
+      // println!("Cannot insert article for {}: {}", tkr, err);
+      failed += 1;
+    }
   }
 
-  Ok(())
+  match failed {
+    0 => Ok(()),
+    _ => Err(format!("{} articles for {} could not be stored", failed, tkr).into()),
+  }
 }
 
 fn process_article(
@@ -118,11 +311,10 @@ fn process_article(
   s_id: &i64,
   _tkr: &String,
   article: RawFeed,
-  overview_id: i32,
+  overview_id: Option<i32>,
   params: &mut Params,
   symbol_log: &mut BufWriter<File>,
 ) -> Result<(), Box<dyn Error>> {
-  let author_id: i32;
   // let mut topic_id: i32;
   let source_id: i32;
 
@@ -152,30 +344,23 @@ fn process_article(
       "No source id",
     )));
   }
-  let mut first_author_name = "_None_".to_string();
-  let auths = params.authors.clone();
-  if auths.is_empty() {
-    let auth = insert_author(conn, article.authors[0].clone())?;
-    params
-      .authors
-      .insert(auth.author_name.clone(), auth.id.clone());
-    author_id = auth.id;
-  } else {
-    if article.authors.len() > 0 {
-      first_author_name = article.authors[0].to_string();
-    }
-
-    if let Some(&tauthor_id) = params.authors.get(&first_author_name) {
-      author_id = tauthor_id;
-    } else {
-      // Author doesn't exist in the map, insert it
-      let auth = insert_author(conn, first_author_name)?;
-      params
-        .authors
-        .insert(auth.author_name.clone(), auth.id.clone());
-      author_id = auth.id;
+  let mut author_ids: Vec<i32> = Vec::new();
+  for name in article
+    .authors
+    .iter()
+    .filter_map(|a| normalize_author_name(a))
+  {
+    let id = get_author_id(conn, &name, params)?;
+    if !author_ids.contains(&id) {
+      author_ids.push(id);
     }
   }
+  // articles.author is required, so articles without a byline point at a placeholder author that
+  // is never mapped in authormaps.
+  let author_id = match author_ids.first() {
+    Some(id) => *id,
+    None => get_author_id(conn, NO_AUTHOR, params)?,
+  };
 
   for topic in article.topics.clone() {
     if params.topics.contains_key(&topic.topic) {
@@ -188,79 +373,80 @@ fn process_article(
     }
   }
 
-  if let Ok(art) = insert_article(
-    conn,
-    source_id,
-    article.category_within_source,
-    article.title,
-    article.url,
-    article.summary,
-    article.banner_image,
-    author_id,
-    article.time_published,
-  ) {
-    if let Ok(feed) = insert_feed(
+  // Sources, authors and topics are cached in params, so they are committed on their own. The
+  // article and everything hanging off its feed row are written together or not at all.
+  conn.transaction::<_, Box<dyn Error>, _>(|conn| {
+    let art = insert_article(
       conn,
-      &s_id.clone(),
+      source_id,
+      article.category_within_source,
+      article.title,
+      article.url,
+      article.summary,
+      article.banner_image,
+      author_id,
+      article.time_published,
+    )?;
+    let feed = insert_feed(
+      conn,
+      s_id,
       overview_id,
       art.hashid,
       source_id,
       article.overall_sentiment_score,
-      article.overall_sentiment_label,
-    ) {
-      if let Ok(_ts) = load_sentiments(conn, article.ticker_sentiment, params, feed.id, symbol_log)
-      {
-        // todo: Improve logging
-        // println!("Inserted sentiments for {}", art.title);
-      } else {
-        // todo: Improve logging
-        // println!("Cannot insert sentiments for {}", art.title);
-      }
-      if let Ok(_tp) = load_topic_map(conn, s_id, article.topics, feed.id, params) {
-        // todo: Improve logging
-        // println!("Inserted topics for {}", art.title);
-      } else {
-        // todo: Improve logging
-
-        // println!("Cannot insert topics for {}", art.title);
-      }
-      if let Ok(_am) = insert_author_map(conn, feed.id, author_id) {
-        // todo: Improve logging
-        // println!("Inserted author map for {}", art.title);
-      } else {
-        // todo: Improve logging
-        // println!("Cannot insert author map for {}", art.title);
-      }
-    } else {
-      // todo: Improve logging
-      // println!("Cannot insert feed {} for sid {}", art.title, s_id);
-      return Err(Box::new(std::io::Error::new(
-        std::io::ErrorKind::Other,
-        "Cannot insert article",
-      )));
+      sentiment_label(
+        &article.overall_sentiment_label,
+        article.overall_sentiment_score,
+        &params.thresholds,
+      ),
+    )?;
+    load_sentiments(
+      conn,
+      s_id,
+      article.ticker_sentiment,
+      params,
+      feed.id,
+      symbol_log,
+    )?;
+    load_topic_map(conn, s_id, article.topics, feed.id, params)?;
+    for author in &author_ids {
+      insert_author_map(conn, feed.id, *author)?;
     }
-  } else {
-    // todo: Improve logging
-    // println!("Cannot insert  for sid {}", s_id);
-    return Err(Box::new(std::io::Error::new(
-      std::io::ErrorKind::Other,
-      "Cannot insert article",
-    )));
-  }
+    Ok(())
+  })
+}
 
-  Ok(())
+/// Looks up the id of a normalized author name, inserting the author if it is new. Names that
+/// differ only in case resolve to the same author.
+fn get_author_id(
+  conn: &mut PgConnection,
+  name: &str,
+  params: &mut Params,
+) -> Result<i32, Box<dyn Error>> {
+  if let Some(&id) = params.authors.get(name) {
+    return Ok(id);
+  }
+  let auth = match find_author_by_name(conn, name)? {
+    Some(auth) => auth,
+    None => insert_author(conn, name.to_string())?,
+  };
+  params.authors.insert(name.to_string(), auth.id);
+  Ok(auth.id)
 }
 
+/// Records the sentiment of the feed's own ticker. The other tickers of the article get theirs
+/// through their own feed rows.
 fn load_sentiments(
   conn: &mut PgConnection,
+  s_id: &i64,
   sentiments: Vec<TickerSentiment>,
   params: &mut Params,
   inp_feed_id: i32,
   symbol_log: &mut BufWriter<File>,
 ) -> Result<(), Box<dyn Error>> {
   for sent in sentiments {
-    let sent_label = sent.ticker_sentiment_label;
     let sent_score = sent.ticker_sentiment_score.parse::<f64>()?;
+    let sent_label = sentiment_label(&sent.ticker_sentiment_label, sent_score, &params.thresholds);
     let sent_rel = sent.relevance_score.parse::<f64>()?;
     let sent_tkr = sent.ticker.clone();
     let sid = params.names_to_sid.get(&sent_tkr).unwrap_or(&-1);
@@ -271,11 +457,21 @@ fn load_sentiments(
       log_missed_symbol(symbol_log, &sent_tkr)?;
       continue;
     }
-    _ = ins_ticker_sentiment(conn, sid, inp_feed_id, sent_rel, sent_score, sent_label)
+    if sid != s_id {
+      continue;
+    }
+    ins_ticker_sentiment(conn, sid, inp_feed_id, sent_rel, sent_score, sent_label)?;
   }
   Ok(())
 }
 
+/// Parses a sentiment label, deriving it from the score when the label is not a known one.
+fn sentiment_label(label: &str, score: f64, thresholds: &SentimentThresholds) -> SentimentLabel {
+  label
+    .parse::<SentimentLabel>()
+    .unwrap_or_else(|_| thresholds.label(score))
+}
+
 fn load_topic_map(
   conn: &mut PgConnection,
   inp_sid: &i64,
@@ -289,7 +485,7 @@ fn load_topic_map(
       println!("Cannot find topic id for {}", topic.topic);
       continue;
     }
-    let _ = ins_topic_map(
+    ins_topic_map(
       conn,
       *inp_sid,
       inp_feed_id,
@@ -299,3 +495,42 @@ fn load_topic_map(
   }
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn article(url: &str, tickers: &[&str]) -> RawFeed {
+    RawFeed {
+      url: url.to_string(),
+      time_published: "20240102T093000".to_string(),
+      ticker_sentiment: tickers
+        .iter()
+        .map(|t| TickerSentiment {
+          ticker: t.to_string(),
+          ..Default::default()
+        })
+        .collect(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_group_feed_by_sid() {
+    let names_to_sid: HashMap<String, i64> = [("AAPL".to_string(), 1), ("MSFT".to_string(), 2)]
+      .into_iter()
+      .collect();
+    let feed = vec![
+      article("a", &["AAPL", "MSFT", "XYZ"]),
+      article("b", &["MSFT", "MSFT"]),
+      article("c", &["XYZ"]),
+    ];
+
+    let groups = group_feed_by_sid(&feed, &names_to_sid);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[&1].0, "AAPL");
+    assert_eq!(groups[&1].1.len(), 1);
+    let msft: Vec<&str> = groups[&2].1.iter().map(|a| a.url.as_str()).collect();
+    assert_eq!(msft, vec!["a", "b"]);
+  }
+}
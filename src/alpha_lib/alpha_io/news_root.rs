@@ -28,7 +28,7 @@
  */
 
 use bincode::Options;
-use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::Utc;
 use crc32fast::Hasher;
 use serde::Serialize;
 
@@ -45,43 +45,80 @@ pub enum Error {
   #[error("Unexpected error: {0}")]
   UnEx(String),
 }
+/// Records a feed snapshot of a sid, along with the sentiment definition the feed was labelled
+/// with. A snapshot that was recorded before for the sid returns its existing overview with
+/// `last_seen` moved to now.
 pub fn insert_news_root(
   conn: &mut PgConnection,
   s_id: i64,
   item_count: i32,
-  news: Vec<RawFeed>,
+  definition_id: Option<i32>,
+  feed_hash: &str,
 ) -> Result<NewsOverview, Error> {
-  let local: DateTime<Local> = Local::now();
-  let date = NaiveDate::from_ymd_opt(local.year(), local.month(), local.day())
-    .unwrap_or(NaiveDate::from_ymd_opt(1900, 1, 1).unwrap());
-  let tim = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
-  let creattion_date = NaiveDateTime::new(date, tim);
+  use diesel::upsert::excluded;
+
+  use crate::schema::newsoverviews::dsl::{hashid, items, last_seen, sid};
+
+  let creation_date = Utc::now();
 
   let rt = NewNewsOverview {
     items: &item_count,
     sid: s_id.clone(),
-    hashid: &get_hash_id(news),
-    creation: &creattion_date,
+    hashid: &feed_hash.to_string(),
+    creation: &creation_date,
+    definitionid: definition_id,
   };
 
   diesel::insert_into(newsoverviews)
     .values(&rt)
+    .on_conflict((hashid, sid))
+    .do_update()
+    .set((items.eq(excluded(items)), last_seen.eq(creation_date)))
     .get_result(conn)
     .map_err(Error::from)
 }
 
-fn get_hash_id(news: Vec<RawFeed>) -> String {
+/// Returns the feed snapshot of a sid that was returned last. A feed that goes back to an earlier
+/// snapshot has that snapshot's `last_seen` moved on, so it is the latest again.
+pub fn get_latest_news_root(
+  conn: &mut PgConnection,
+  s_id: i64,
+) -> Result<Option<NewsOverview>, Error> {
+  use crate::schema::newsoverviews::dsl::{id, last_seen, sid};
+
+  newsoverviews
+    .filter(sid.eq(s_id))
+    .order((last_seen.desc(), id.desc()))
+    .first::<NewsOverview>(conn)
+    .optional()
+    .map_err(Error::from)
+}
+
+/// Moves the `last_seen` time of a feed snapshot to now.
+pub fn touch_news_root(conn: &mut PgConnection, overview_id: i32) -> Result<(), Error> {
+  use crate::schema::newsoverviews::dsl::last_seen;
+
+  diesel::update(newsoverviews.find(overview_id))
+    .set(last_seen.eq(Utc::now()))
+    .execute(conn)
+    .map(|_| ())
+    .map_err(Error::from)
+}
+
+/// The CRC32 checksum of a feed, identifying a snapshot.
+pub fn feed_hash(news: &[RawFeed]) -> String {
   let bytes = convert_to_bytes(news);
   calculate_checksum(&bytes)
 }
-fn convert_to_bytes<T>(vec: Vec<T>) -> Vec<u8>
+
+fn convert_to_bytes<T>(items: &[T]) -> Vec<u8>
 where
   T: Serialize,
 {
   let mut bytes = Vec::new();
-  for item in vec {
+  for item in items {
     // Correct usage with the default options
-    let serialized = bincode::options().serialize(&item).unwrap();
+    let serialized = bincode::options().serialize(item).unwrap();
     bytes.extend(serialized);
   }
   bytes
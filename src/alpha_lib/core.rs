@@ -29,6 +29,13 @@
 
 pub mod alpha_data_types;
 pub mod alpha_funcs;
+pub mod decimal;
+pub mod event_study;
+pub mod market_calendar;
+pub mod market_status;
+pub mod news_identity;
+pub mod news_query;
 pub mod news_type;
+pub mod sentiment_label;
 
 pub mod macros;
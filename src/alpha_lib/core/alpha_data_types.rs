@@ -27,8 +27,11 @@
  * SOFTWARE.
  */
 
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, ToPrimitive};
 use chrono::NaiveDate;
-use serde::Deserialize;
+use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 
@@ -40,6 +43,10 @@ pub enum Error {
   ParseFloat(#[from] std::num::ParseFloatError),
   #[error(transparent)]
   ParseInt(#[from] std::num::ParseIntError),
+  #[error(transparent)]
+  ParseDecimal(#[from] bigdecimal::ParseBigDecimalError),
+  #[error("Volume out of range: {0}")]
+  VolumeRange(String),
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -87,55 +94,55 @@ impl AlphaSymbol {
 /// mapping of Company Overview from Alpha Vantage
 /// This is split into two structs to allow for easier use of the data and to avoid long
 ///compile times with the  Diesel 64-column-tables feature enabled
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FullOverview {
   /// based on the Alpha Vantage Company Overview endpoint https://www.alphavantage.co/query?function=OVERVIEW&symbol=IBM&apikey=demo
   pub sid: i64,
   pub symbol: String,
-  pub name: String,
-  pub description: String,
-  pub cik: String,
-  pub exch: String,
-  pub curr: String,
-  pub country: String,
-  pub sector: String,
-  pub industry: String,
-  pub address: String,
-  pub fiscalyearend: String,
-  pub latestquarter: NaiveDate,
-  pub marketcapitalization: i64,
-  pub ebitda: i64,
-  pub peratio: f32,
-  pub pegratio: f32,
-  pub bookvalue: f64,
-  pub dividendpershare: f32,
-  pub dividendyield: f32,
-  pub eps: f32,
-  pub revenuepersharettm: f32,
-  pub profitmargin: f32,
-  pub operatingmarginttm: f32,
-  pub returnonassetsttm: f32,
-  pub returnonequityttm: f32,
-  pub revenuettm: i64,
-  pub grossprofitttm: i64,
-  pub dilutedepsttm: f32,
-  pub quarterlyearningsgrowthyoy: f32,
-  pub quarterlyrevenuegrowthyoy: f32,
-  pub analysttargetprice: f32,
-  pub trailingpe: f32,
-  pub forwardpe: f32,
-  pub pricetosalesratiottm: f32,
-  pub pricetobookratio: f32,
-  pub evtorevenue: f32,
-  pub evtoebitda: f32,
-  pub beta: f64,
-  pub annweekhigh: f64,
-  pub annweeklow: f64,
-  pub fiftydaymovingaverage: f64,
-  pub twohdaymovingaverage: f64,
-  pub sharesoutstanding: f64,
-  pub dividenddate: NaiveDate,
-  pub exdividenddate: NaiveDate,
+  pub name: Option<String>,
+  pub description: Option<String>,
+  pub cik: Option<String>,
+  pub exch: Option<String>,
+  pub curr: Option<String>,
+  pub country: Option<String>,
+  pub sector: Option<String>,
+  pub industry: Option<String>,
+  pub address: Option<String>,
+  pub fiscalyearend: Option<String>,
+  pub latestquarter: Option<NaiveDate>,
+  pub marketcapitalization: Option<i64>,
+  pub ebitda: Option<i64>,
+  pub peratio: Option<BigDecimal>,
+  pub pegratio: Option<BigDecimal>,
+  pub bookvalue: Option<BigDecimal>,
+  pub dividendpershare: Option<BigDecimal>,
+  pub dividendyield: Option<BigDecimal>,
+  pub eps: Option<BigDecimal>,
+  pub revenuepersharettm: Option<BigDecimal>,
+  pub profitmargin: Option<BigDecimal>,
+  pub operatingmarginttm: Option<BigDecimal>,
+  pub returnonassetsttm: Option<BigDecimal>,
+  pub returnonequityttm: Option<BigDecimal>,
+  pub revenuettm: Option<i64>,
+  pub grossprofitttm: Option<i64>,
+  pub dilutedepsttm: Option<BigDecimal>,
+  pub quarterlyearningsgrowthyoy: Option<BigDecimal>,
+  pub quarterlyrevenuegrowthyoy: Option<BigDecimal>,
+  pub analysttargetprice: Option<BigDecimal>,
+  pub trailingpe: Option<BigDecimal>,
+  pub forwardpe: Option<BigDecimal>,
+  pub pricetosalesratiottm: Option<BigDecimal>,
+  pub pricetobookratio: Option<BigDecimal>,
+  pub evtorevenue: Option<BigDecimal>,
+  pub evtoebitda: Option<BigDecimal>,
+  pub beta: Option<f64>,
+  pub annweekhigh: Option<BigDecimal>,
+  pub annweeklow: Option<BigDecimal>,
+  pub fiftydaymovingaverage: Option<BigDecimal>,
+  pub twohdaymovingaverage: Option<BigDecimal>,
+  pub sharesoutstanding: Option<i64>,
+  pub dividenddate: Option<NaiveDate>,
+  pub exdividenddate: Option<NaiveDate>,
 }
 
 // JSON: Object {"200DayMovingAverage": String("151.55"),
@@ -189,104 +196,146 @@ pub struct FullOverview {
 // "SharesOutstanding": String("15728700000"),
 // "Symbol": String("AAPL"),
 // "TrailingPE": String("29.11")}
-impl FullOverview {
-  /// a set of helper functions
-  ///  TO BE REFACTORED!!!
-  fn get_string_field(json_txt: &Value, field: &str) -> String {
-    const ERROR: &str = "__Error__";
-    json_txt[field].as_str().unwrap_or(ERROR).to_string()
-  }
+/// A company overview field that was present but could not be parsed.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{field}: cannot parse {value:?}")]
+pub struct FieldError {
+  pub field: &'static str,
+  pub value: String,
+}
 
-  fn get_f32_field(json_txt: &Value, field: &str) -> f32 {
-    json_txt[field]
-      .as_str()
-      .unwrap_or("")
-      .parse::<f32>()
-      .unwrap_or(-9.99)
-  }
+/// Reads the string valued fields of an overview response. Alpha Vantage marks a missing value
+/// with "None", "-" or an empty string; those are read as `None` rather than reported.
+struct FieldReader<'a> {
+  json: &'a Value,
+  errors: Vec<FieldError>,
+}
 
-  fn get_i64_field(json_txt: &Value, field: &str) -> i64 {
-    json_txt[field]
-      .as_str()
-      .unwrap_or("")
-      .parse::<i64>()
-      .unwrap_or(-999)
-  }
+impl FieldReader<'_> {
+  const MISSING: [&'static str; 3] = ["", "None", "-"];
 
-  fn get_f64_field(json_txt: &Value, field: &str) -> f64 {
-    json_txt[field]
-      .as_str()
-      .unwrap_or("")
-      .parse::<f64>()
-      .unwrap_or(-9.99)
+  fn get<T: FromStr>(&mut self, field: &'static str) -> Option<T> {
+    let value = self.json[field].as_str().map(str::trim)?;
+    if Self::MISSING.contains(&value) {
+      return None;
+    }
+    match value.parse::<T>() {
+      Ok(parsed) => Some(parsed),
+      Err(_) => {
+        self.errors.push(FieldError {
+          field,
+          value: value.to_string(),
+        });
+        None
+      }
+    }
   }
+}
 
-  fn get_date_field(json_txt: &Value, field: &str) -> NaiveDate {
-    NaiveDate::parse_from_str(json_txt[field].as_str().unwrap_or(""), "%Y-%m-%d")
-      .unwrap_or(NaiveDate::from_ymd_opt(1900, 1, 1).unwrap())
-  }
-  pub fn new(sid: i64, json_txt: Value) -> Option<Self> {
-    Some(Self {
+impl FullOverview {
+  /// Builds an overview from the response of the OVERVIEW endpoint.
+  ///
+  /// Returns `None` when the response has no symbol. Otherwise the overview is returned with
+  /// the fields that were present but unparseable, which are left as `None`.
+  pub fn new(sid: i64, json_txt: Value) -> Option<(Self, Vec<FieldError>)> {
+    let mut fields = FieldReader {
+      json: &json_txt,
+      errors: Vec::new(),
+    };
+    let symbol = fields.get::<String>("Symbol")?;
+    let overview = Self {
       sid,
-      symbol: Self::get_string_field(&json_txt, "Symbol"),
-      name: Self::get_string_field(&json_txt, "Name"),
-      description: Self::get_string_field(&json_txt, "Description"),
-      cik: Self::get_string_field(&json_txt, "CIK"),
-      exch: Self::get_string_field(&json_txt, "Exchange"),
-      curr: Self::get_string_field(&json_txt, "Currency"),
-      country: Self::get_string_field(&json_txt, "Country"),
-      sector: Self::get_string_field(&json_txt, "Sector"),
-      industry: Self::get_string_field(&json_txt, "Industry"),
-      address: Self::get_string_field(&json_txt, "Address"),
-      fiscalyearend: Self::get_string_field(&json_txt, "FiscalYearEnd"),
-      latestquarter: Self::get_date_field(&json_txt, "LatestQuarter"),
-      marketcapitalization: Self::get_i64_field(&json_txt, "MarketCapitalization"),
-      ebitda: Self::get_i64_field(&json_txt, "EBITDA"),
-      peratio: Self::get_f32_field(&json_txt, "PERatio"),
-      pegratio: Self::get_f32_field(&json_txt, "PEGRatio"),
-      bookvalue: Self::get_f64_field(&json_txt, "BookValue"),
-      dividendpershare: Self::get_f32_field(&json_txt, "DividendPerShare"),
-      dividendyield: Self::get_f32_field(&json_txt, "DividendYield"),
-      eps: Self::get_f32_field(&json_txt, "EPS"),
-      revenuepersharettm: Self::get_f32_field(&json_txt, "RevenuePerShareTTM"),
-      profitmargin: Self::get_f32_field(&json_txt, "ProfitMargin"),
-      operatingmarginttm: Self::get_f32_field(&json_txt, "OperatingMarginTTM"),
-      returnonassetsttm: Self::get_f32_field(&json_txt, "ReturnOnAssetsTTM"),
-      returnonequityttm: Self::get_f32_field(&json_txt, "ReturnOnEquityTTM"),
-      revenuettm: Self::get_i64_field(&json_txt, "RevenueTTM"),
-      grossprofitttm: Self::get_i64_field(&json_txt, "GrossProfitTTM"),
-      dilutedepsttm: Self::get_f32_field(&json_txt, "DilutedEPSTTM"),
-      quarterlyearningsgrowthyoy: Self::get_f32_field(&json_txt, "QuarterlyEarningsGrowthYOY"),
-      quarterlyrevenuegrowthyoy: Self::get_f32_field(&json_txt, "QuarterlyRevenueGrowthYOY"),
-      analysttargetprice: Self::get_f32_field(&json_txt, "AnalystTargetPrice"),
-      trailingpe: Self::get_f32_field(&json_txt, "TrailingPE"),
-      forwardpe: Self::get_f32_field(&json_txt, "ForwardPE"),
-      pricetosalesratiottm: Self::get_f32_field(&json_txt, "PriceToSalesRatioTTM"),
-      pricetobookratio: Self::get_f32_field(&json_txt, "PriceToBookRatio"),
-      evtorevenue: Self::get_f32_field(&json_txt, "EVToRevenue"),
-      evtoebitda: Self::get_f32_field(&json_txt, "EVToEBITDA"),
-      beta: Self::get_f64_field(&json_txt, "Beta"),
-      annweekhigh: Self::get_f64_field(&json_txt, "52WeekHigh"),
-      annweeklow: Self::get_f64_field(&json_txt, "52WeekLow"),
-      fiftydaymovingaverage: Self::get_f64_field(&json_txt, "50DayMovingAverage"),
-      twohdaymovingaverage: Self::get_f64_field(&json_txt, "200DayMovingAverage"),
-      sharesoutstanding: Self::get_f64_field(&json_txt, "SharesOutstanding"),
-      dividenddate: Self::get_date_field(&json_txt, "DividendDate"),
-      exdividenddate: Self::get_date_field(&json_txt, "ExDividendDate"),
+      symbol,
+      name: fields.get("Name"),
+      description: fields.get("Description"),
+      cik: fields.get("CIK"),
+      exch: fields.get("Exchange"),
+      curr: fields.get("Currency"),
+      country: fields.get("Country"),
+      sector: fields.get("Sector"),
+      industry: fields.get("Industry"),
+      address: fields.get("Address"),
+      fiscalyearend: fields.get("FiscalYearEnd"),
+      latestquarter: fields.get("LatestQuarter"),
+      marketcapitalization: fields.get("MarketCapitalization"),
+      ebitda: fields.get("EBITDA"),
+      peratio: fields.get("PERatio"),
+      pegratio: fields.get("PEGRatio"),
+      bookvalue: fields.get("BookValue"),
+      dividendpershare: fields.get("DividendPerShare"),
+      dividendyield: fields.get("DividendYield"),
+      eps: fields.get("EPS"),
+      revenuepersharettm: fields.get("RevenuePerShareTTM"),
+      profitmargin: fields.get("ProfitMargin"),
+      operatingmarginttm: fields.get("OperatingMarginTTM"),
+      returnonassetsttm: fields.get("ReturnOnAssetsTTM"),
+      returnonequityttm: fields.get("ReturnOnEquityTTM"),
+      revenuettm: fields.get("RevenueTTM"),
+      grossprofitttm: fields.get("GrossProfitTTM"),
+      dilutedepsttm: fields.get("DilutedEPSTTM"),
+      quarterlyearningsgrowthyoy: fields.get("QuarterlyEarningsGrowthYOY"),
+      quarterlyrevenuegrowthyoy: fields.get("QuarterlyRevenueGrowthYOY"),
+      analysttargetprice: fields.get("AnalystTargetPrice"),
+      trailingpe: fields.get("TrailingPE"),
+      forwardpe: fields.get("ForwardPE"),
+      pricetosalesratiottm: fields.get("PriceToSalesRatioTTM"),
+      pricetobookratio: fields.get("PriceToBookRatio"),
+      evtorevenue: fields.get("EVToRevenue"),
+      evtoebitda: fields.get("EVToEBITDA"),
+      beta: fields.get("Beta"),
+      annweekhigh: fields.get("52WeekHigh"),
+      annweeklow: fields.get("52WeekLow"),
+      fiftydaymovingaverage: fields.get("50DayMovingAverage"),
+      twohdaymovingaverage: fields.get("200DayMovingAverage"),
+      sharesoutstanding: fields.get("SharesOutstanding"),
+      dividenddate: fields.get("DividendDate"),
+      exdividenddate: fields.get("ExDividendDate"),
+    };
+    Some((overview, fields.errors))
+  }
+}
+
+/// A traded volume. Equity volumes are whole shares; crypto volumes are quoted with decimals, those
+/// are kept exactly in `fractional` and rounded in `whole`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Volume {
+  pub whole: i64,
+  pub fractional: Option<BigDecimal>,
+}
+
+impl FromStr for Volume {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let volume = s.trim().parse::<BigDecimal>()?;
+    let whole = volume
+      .round(0)
+      .to_i64()
+      .ok_or_else(|| Error::VolumeRange(s.to_string()))?;
+    Ok(Self {
+      whole,
+      fractional: (!volume.is_integer()).then_some(volume),
     })
   }
 }
 
+impl<'de> Deserialize<'de> for Volume {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    text.parse().map_err(de::Error::custom)
+  }
+}
+
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct RawIntraDayPrice {
   ///This is for the TIME_SERIES_INTRADAY endpoint
   /// based on https://www.alphavantage.co/query?function=TIME_SERIES_INTRADAY&symbol=IBM&interval=5min&apikey=demo&datatype=csv
   pub timestamp: String,
-  pub open: f32,
-  pub high: f32,
-  pub low: f32,
-  pub close: f32,
-  pub volume: i32,
+  pub open: BigDecimal,
+  pub high: BigDecimal,
+  pub low: BigDecimal,
+  pub close: BigDecimal,
+  pub volume: Volume,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -295,11 +344,11 @@ pub struct RawDailyPrice {
   /// https://www.alphavantage.co/query?function=TIME_SERIES_DAILY&symbol=IBM&apikey=demo
   pub date: NaiveDate,
   pub symbol: String,
-  pub open: f32,
-  pub high: f32,
-  pub low: f32,
-  pub close: f32,
-  pub volume: i32,
+  pub open: BigDecimal,
+  pub high: BigDecimal,
+  pub low: BigDecimal,
+  pub close: BigDecimal,
+  pub volume: Volume,
 }
 
 pub enum TopType {
@@ -364,10 +413,10 @@ pub struct MostActivelyTraded {
 pub struct GTopStat {
   ///Generic Top Statistics had to change name to avoid confusion with dbmodeal::TopSat
   pub ticker: String,
-  pub price: f32,
-  pub change_amount: f32,
-  pub change_percentage: f32,
-  pub volume: i32,
+  pub price: BigDecimal,
+  pub change_amount: BigDecimal,
+  pub change_percentage: BigDecimal,
+  pub volume: i64,
 }
 
 pub trait Convert {
@@ -381,10 +430,10 @@ macro_rules! impl_convert {
         let cleaned = self.change_percentage.trim_end_matches('%');
         Ok(GTopStat {
           ticker: self.ticker.clone(),
-          price: self.price.parse::<f32>()?,
-          change_amount: self.change_amount.parse::<f32>()?,
-          change_percentage: cleaned.parse::<f32>()?,
-          volume: self.volume.parse::<i32>()?,
+          price: self.price.parse::<BigDecimal>()?,
+          change_amount: self.change_amount.parse::<BigDecimal>()?,
+          change_percentage: cleaned.parse::<BigDecimal>()?,
+          volume: self.volume.parse::<i64>()?,
         })
       }
     }
@@ -402,6 +451,11 @@ impl_convert!(MostActivelyTraded);
 #[cfg(test)]
 mod tests {
   use super::*;
+
+  fn dec(value: &str) -> BigDecimal {
+    BigDecimal::from_str(value).unwrap()
+  }
+
   #[derive(Debug, Clone)]
   struct MockTopStat {
     pub ticker: String,
@@ -426,9 +480,9 @@ mod tests {
 
     let stat = gainer.make_top_stat().unwrap();
     assert_eq!(stat.ticker, "TEST");
-    assert_eq!(stat.price, 10.5);
-    assert_eq!(stat.change_amount, 1.5);
-    assert_eq!(stat.change_percentage, 15.0);
+    assert_eq!(stat.price, dec("10.5"));
+    assert_eq!(stat.change_amount, dec("1.5"));
+    assert_eq!(stat.change_percentage, dec("15.0"));
     assert_eq!(stat.volume, 1000);
   }
 
@@ -445,9 +499,9 @@ mod tests {
     let result = mock.make_top_stat().unwrap();
 
     assert_eq!(result.ticker, "AAPL");
-    assert_eq!(result.price, 150.75);
-    assert_eq!(result.change_amount, 5.25);
-    assert_eq!(result.change_percentage, 3.5);
+    assert_eq!(result.price, dec("150.75"));
+    assert_eq!(result.change_amount, dec("5.25"));
+    assert_eq!(result.change_percentage, dec("3.5"));
     assert_eq!(result.volume, 1000000);
   }
 
@@ -535,6 +589,94 @@ mod tests {
 
     let result = mock.make_top_stat().unwrap();
 
-    assert_eq!(result.change_percentage, 3.5);
+    assert_eq!(result.change_percentage, dec("3.5"));
+  }
+
+  #[test]
+  fn test_full_overview_missing_fields_are_none() {
+    let json = serde_json::json!({
+      "Symbol": "AAPL",
+      "Sector": "None",
+      "PERatio": "29.11",
+      "PEGRatio": "-",
+      "MarketCapitalization": "2701718979000",
+      "DividendDate": "None",
+      "ExDividendDate": "2023-05-12",
+    });
+
+    let (overview, errors) = FullOverview::new(1, json).unwrap();
+
+    assert!(errors.is_empty());
+    assert_eq!(overview.symbol, "AAPL");
+    assert_eq!(overview.sector, None);
+    assert_eq!(overview.peratio, Some(dec("29.11")));
+    assert_eq!(overview.pegratio, None);
+    assert_eq!(overview.marketcapitalization, Some(2_701_718_979_000));
+    assert_eq!(overview.eps, None);
+    assert_eq!(overview.dividenddate, None);
+    assert_eq!(
+      overview.exdividenddate,
+      NaiveDate::from_ymd_opt(2023, 5, 12)
+    );
+  }
+
+  #[test]
+  fn test_full_overview_reports_unparseable_fields() {
+    let json = serde_json::json!({
+      "Symbol": "AAPL",
+      "Beta": "high",
+      "LatestQuarter": "2023-13-31",
+    });
+
+    let (overview, errors) = FullOverview::new(1, json).unwrap();
+
+    assert_eq!(overview.beta, None);
+    assert_eq!(overview.latestquarter, None);
+    assert_eq!(
+      errors,
+      vec![
+        FieldError {
+          field: "LatestQuarter",
+          value: "2023-13-31".to_string(),
+        },
+        FieldError {
+          field: "Beta",
+          value: "high".to_string(),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_full_overview_requires_symbol() {
+    let json = serde_json::json!({ "Name": "Apple Inc" });
+    assert!(FullOverview::new(1, json).is_none());
+  }
+
+  #[test]
+  fn test_volume_parsing() {
+    let equity = "3000000000".parse::<Volume>().unwrap();
+    assert_eq!(equity.whole, 3_000_000_000);
+    assert_eq!(equity.fractional, None);
+
+    let crypto = "1289.61232".parse::<Volume>().unwrap();
+    assert_eq!(crypto.whole, 1290);
+    assert_eq!(crypto.fractional, Some(dec("1289.61232")));
+
+    assert!("99999999999999999999".parse::<Volume>().is_err());
+    assert!("n/a".parse::<Volume>().is_err());
+  }
+
+  #[test]
+  fn test_intraday_csv_with_fractional_volume() {
+    let text = "timestamp,open,high,low,close,volume\n\
+                2024-03-01 10:00:00,61000.5,61010.25,60990.1,61005.75,12.3456\n";
+    let ticks = csv::Reader::from_reader(text.as_bytes())
+      .deserialize()
+      .collect::<Result<Vec<RawIntraDayPrice>, _>>()
+      .unwrap();
+    assert_eq!(ticks[0].close, dec("61005.75"));
+    assert_eq!(ticks[0].volume.whole, 12);
+    assert_eq!(ticks[0].volume.fractional, Some(dec("12.3456")));
   }
 }
@@ -73,6 +73,49 @@ pub fn normalize_alpha_region(reg: &str) -> String {
   .to_string()
 }
 
+/// Normalizes an author name from the news feed.
+///
+/// Whitespace is collapsed, a leading "By" is removed and names written entirely in upper or
+/// lower case are title cased, so "  BY  JOHN SMITH " and "John Smith" map to the same author.
+/// Mixed case names such as "McKenzie" are kept as they are.
+///
+/// # Returns
+///
+/// * `None` if nothing is left of the name.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(normalize_author_name("By jane doe"), Some("Jane Doe".to_string()));
+/// ```
+pub fn normalize_author_name(name: &str) -> Option<String> {
+  let mut words: Vec<&str> = name.split_whitespace().collect();
+  if words.len() > 1 && words[0].trim_end_matches(':').eq_ignore_ascii_case("by") {
+    words.remove(0);
+  }
+  let name = words.join(" ");
+  if name.is_empty() {
+    return None;
+  }
+
+  let has_upper = name.chars().any(char::is_uppercase);
+  let has_lower = name.chars().any(char::is_lowercase);
+  if has_upper && has_lower {
+    return Some(name);
+  }
+  let mut title = String::with_capacity(name.len());
+  let mut start_of_word = true;
+  for c in name.chars() {
+    if start_of_word {
+      title.extend(c.to_uppercase());
+    } else {
+      title.extend(c.to_lowercase());
+    }
+    start_of_word = c.is_whitespace() || c == '-' || c == '\'';
+  }
+  Some(title)
+}
+
 pub fn top_constants(act: &TopType) -> String {
   match act {
     TopType::TopGainer => "GAIN",
@@ -99,4 +142,26 @@ mod tests {
     let short_region = normalize_alpha_region(region);
     assert_eq!(short_region, "Whatever");
   }
+
+  #[test]
+  fn test_normalize_author_name() {
+    assert_eq!(
+      normalize_author_name("  BY  JOHN   SMITH "),
+      Some("John Smith".to_string())
+    );
+    assert_eq!(
+      normalize_author_name("by: jean-luc o'neil"),
+      Some("Jean-Luc O'Neil".to_string())
+    );
+    assert_eq!(
+      normalize_author_name("Zacks Equity Research"),
+      Some("Zacks Equity Research".to_string())
+    );
+    assert_eq!(
+      normalize_author_name("McKenzie"),
+      Some("McKenzie".to_string())
+    );
+    assert_eq!(normalize_author_name("By"), Some("By".to_string()));
+    assert_eq!(normalize_author_name("   "), None);
+  }
 }
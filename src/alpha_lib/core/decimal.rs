@@ -0,0 +1,119 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! `f64` views of the `BigDecimal` prices and fundamentals, for statistics and charts.
+
+use bigdecimal::{BigDecimal, ToPrimitive};
+
+use crate::{
+  alpha_lib::core::alpha_data_types::{RawDailyPrice, RawIntraDayPrice},
+  db_models::{IntraDayPrice, SummaryPrice},
+};
+
+/// Lossy conversion of a decimal to `f64`.
+pub trait AsF64 {
+  type Output;
+
+  fn as_f64(&self) -> Self::Output;
+}
+
+impl AsF64 for BigDecimal {
+  type Output = f64;
+
+  /// Returns `NaN` when the decimal is outside the range of `f64`.
+  fn as_f64(&self) -> f64 {
+    self.to_f64().unwrap_or(f64::NAN)
+  }
+}
+
+impl AsF64 for Option<BigDecimal> {
+  type Output = Option<f64>;
+
+  fn as_f64(&self) -> Option<f64> {
+    self.as_ref().map(AsF64::as_f64)
+  }
+}
+
+/// The prices of a bar as `f64`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ohlc {
+  pub open: f64,
+  pub high: f64,
+  pub low: f64,
+  pub close: f64,
+}
+
+macro_rules! impl_ohlc_from {
+  ($type:ty) => {
+    impl From<&$type> for Ohlc {
+      fn from(bar: &$type) -> Self {
+        Self {
+          open: bar.open.as_f64(),
+          high: bar.high.as_f64(),
+          low: bar.low.as_f64(),
+          close: bar.close.as_f64(),
+        }
+      }
+    }
+  };
+}
+
+impl_ohlc_from!(IntraDayPrice);
+impl_ohlc_from!(SummaryPrice);
+impl_ohlc_from!(RawIntraDayPrice);
+impl_ohlc_from!(RawDailyPrice);
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+
+  #[test]
+  fn test_as_f64() {
+    let price = BigDecimal::from_str("4235.123456789").unwrap();
+    assert_eq!(price.as_f64(), 4235.123456789);
+    assert_eq!(Some(price).as_f64(), Some(4235.123456789));
+    assert_eq!(None::<BigDecimal>.as_f64(), None);
+  }
+
+  #[test]
+  fn test_ohlc_from_bar() {
+    let bar = RawIntraDayPrice {
+      open: BigDecimal::from_str("0.000012345").unwrap(),
+      high: BigDecimal::from_str("0.00001240").unwrap(),
+      low: BigDecimal::from_str("0.0000123").unwrap(),
+      close: BigDecimal::from_str("0.00001238").unwrap(),
+      ..Default::default()
+    };
+    let ohlc = Ohlc::from(&bar);
+    assert_eq!(ohlc.open, 0.000012345);
+    assert_eq!(ohlc.close, 0.00001238);
+  }
+}
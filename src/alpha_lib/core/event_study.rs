@@ -0,0 +1,348 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Forward price returns after news articles, for studying how prices react to sentiment.
+
+use std::{fmt, io, str::FromStr};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+
+use crate::alpha_lib::core::{
+  market_calendar::{to_utc, ALPHA_VANTAGE_TZ},
+  sentiment_label::SentimentLabel,
+};
+
+/// Upper bounds of the relevance buckets; the last bucket includes 1.0.
+const RELEVANCE_BOUNDS: [(f64, &str); 4] = [
+  (0.25, "0.00-0.25"),
+  (0.5, "0.25-0.50"),
+  (0.75, "0.50-0.75"),
+  (f64::INFINITY, "0.75-1.00"),
+];
+
+/// How far after an article its return is measured, e.g. "5m", "1h" or "1d".
+///
+/// Minute and hour horizons use intraday prices; day horizons count trading days in the daily
+/// closes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Horizon {
+  Minutes(i64),
+  Hours(i64),
+  Days(i64),
+}
+
+impl Horizon {
+  pub fn is_intraday(&self) -> bool {
+    !matches!(self, Horizon::Days(_))
+  }
+
+  /// The wall clock length of an intraday horizon.
+  pub fn duration(&self) -> Duration {
+    match *self {
+      Horizon::Minutes(n) => Duration::minutes(n),
+      Horizon::Hours(n) => Duration::hours(n),
+      Horizon::Days(n) => Duration::days(n),
+    }
+  }
+}
+
+impl fmt::Display for Horizon {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Horizon::Minutes(n) => write!(f, "{}m", n),
+      Horizon::Hours(n) => write!(f, "{}h", n),
+      Horizon::Days(n) => write!(f, "{}d", n),
+    }
+  }
+}
+
+impl FromStr for Horizon {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+    let split = s.char_indices().last().map_or(0, |(i, _)| i);
+    let (count, unit) = s.split_at(split);
+    let count = count
+      .parse::<i64>()
+      .ok()
+      .filter(|n| *n > 0)
+      .ok_or_else(|| format!("Invalid horizon {}", s))?;
+    match unit {
+      "m" => Ok(Horizon::Minutes(count)),
+      "h" => Ok(Horizon::Hours(count)),
+      "d" => Ok(Horizon::Days(count)),
+      _ => Err(format!("Invalid horizon {}", s)),
+    }
+  }
+}
+
+/// The relevance bucket of a ticker relevance score, e.g. "0.50-0.75".
+pub fn relevance_bucket(relevance: f64) -> &'static str {
+  RELEVANCE_BOUNDS
+    .iter()
+    .find(|(bound, _)| relevance < *bound)
+    .map(|(_, name)| *name)
+    .unwrap_or(RELEVANCE_BOUNDS[RELEVANCE_BOUNDS.len() - 1].1)
+}
+
+/// The return from the last intraday close at or before `published` to the last close at or
+/// before `published + horizon`. `ticks` must be sorted by time.
+///
+/// Returns `None` when there is no tick before the article or no trade inside the horizon, e.g.
+/// for an article published overnight.
+pub fn intraday_return(
+  ticks: &[(DateTime<Utc>, f64)],
+  published: DateTime<Utc>,
+  horizon: Horizon,
+) -> Option<f64> {
+  let base = last_at_or_before(ticks, published)?;
+  let end = last_at_or_before(ticks, published + horizon.duration())?;
+  match end > base {
+    true => simple_return(ticks[base].1, ticks[end].1),
+    false => None,
+  }
+}
+
+/// The close-to-close return from the last session that closed at or before `published` to the
+/// close `days` trading days later. `closes` must be sorted by date.
+pub fn daily_return(
+  closes: &[(NaiveDate, f64)],
+  published: DateTime<Utc>,
+  days: i64,
+) -> Option<f64> {
+  let market_close = NaiveTime::from_hms_opt(16, 0, 0)?;
+  let closed = closes.partition_point(|(date, _)| {
+    to_utc(date.and_time(market_close), ALPHA_VANTAGE_TZ).is_some_and(|t| t <= published)
+  });
+  let base = closed.checked_sub(1)?;
+  let end = base + usize::try_from(days).ok()?;
+  simple_return(closes[base].1, closes.get(end)?.1)
+}
+
+fn last_at_or_before(ticks: &[(DateTime<Utc>, f64)], t: DateTime<Utc>) -> Option<usize> {
+  ticks.partition_point(|(time, _)| *time <= t).checked_sub(1)
+}
+
+fn simple_return(from: f64, to: f64) -> Option<f64> {
+  match from > 0.0 {
+    true => Some(to / from - 1.0),
+    false => None,
+  }
+}
+
+/// Summary statistics of a set of returns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnStats {
+  pub count: usize,
+  pub mean: f64,
+  pub median: f64,
+  pub stddev: f64,
+  /// Share of positive returns.
+  pub hit_rate: f64,
+}
+
+impl ReturnStats {
+  /// Returns `None` for an empty set. The standard deviation is the sample deviation, 0.0 for a
+  /// single return.
+  pub fn from_returns(returns: &[f64]) -> Option<ReturnStats> {
+    if returns.is_empty() {
+      return None;
+    }
+    let count = returns.len();
+    let mean = returns.iter().sum::<f64>() / count as f64;
+    let mut sorted = returns.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median = match count % 2 {
+      0 => (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0,
+      _ => sorted[count / 2],
+    };
+    let stddev = match count {
+      1 => 0.0,
+      _ => (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (count - 1) as f64).sqrt(),
+    };
+    let hit_rate = returns.iter().filter(|r| **r > 0.0).count() as f64 / count as f64;
+    Some(ReturnStats {
+      count,
+      mean,
+      median,
+      stddev,
+      hit_rate,
+    })
+  }
+}
+
+/// The measured return of one article over one horizon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventReturn {
+  pub symbol: String,
+  pub article: String,
+  pub published: DateTime<Utc>,
+  pub label: SentimentLabel,
+  pub relevance: f64,
+  pub sentiment: f64,
+  pub horizon: Horizon,
+  pub forward_return: f64,
+}
+
+/// A line of the CSV written by `write_csv`.
+#[derive(Serialize)]
+struct CsvRow<'a> {
+  symbol: &'a str,
+  article: &'a str,
+  published: String,
+  label: String,
+  relevance_bucket: &'static str,
+  relevance: f64,
+  sentiment: f64,
+  horizon: String,
+  #[serde(rename = "return")]
+  forward_return: f64,
+}
+
+/// Writes the event returns as CSV with a header line.
+pub fn write_csv<W: io::Write>(out: W, rows: &[EventReturn]) -> Result<(), csv::Error> {
+  let mut writer = csv::Writer::from_writer(out);
+  for row in rows {
+    writer.serialize(CsvRow {
+      symbol: &row.symbol,
+      article: &row.article,
+      published: row.published.to_rfc3339(),
+      label: row.label.to_string(),
+      relevance_bucket: relevance_bucket(row.relevance),
+      relevance: row.relevance,
+      sentiment: row.sentiment,
+      horizon: row.horizon.to_string(),
+      forward_return: row.forward_return,
+    })?;
+  }
+  writer.flush()?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  fn utc(h: u32, m: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 2, h, m, 0).unwrap()
+  }
+
+  #[test]
+  fn test_horizon() {
+    assert_eq!("5m".parse::<Horizon>(), Ok(Horizon::Minutes(5)));
+    assert_eq!("1h".parse::<Horizon>(), Ok(Horizon::Hours(1)));
+    assert_eq!("2d".parse::<Horizon>(), Ok(Horizon::Days(2)));
+    assert!("0m".parse::<Horizon>().is_err());
+    assert!("5w".parse::<Horizon>().is_err());
+    assert!("".parse::<Horizon>().is_err());
+    assert_eq!(Horizon::Hours(1).to_string(), "1h");
+    assert!(!Horizon::Days(1).is_intraday());
+  }
+
+  #[test]
+  fn test_relevance_bucket() {
+    assert_eq!(relevance_bucket(0.0), "0.00-0.25");
+    assert_eq!(relevance_bucket(0.25), "0.25-0.50");
+    assert_eq!(relevance_bucket(1.0), "0.75-1.00");
+  }
+
+  #[test]
+  fn test_intraday_return() {
+    let ticks = vec![
+      (utc(15, 0), 100.0),
+      (utc(15, 5), 101.0),
+      (utc(16, 0), 110.0),
+    ];
+    let r = intraday_return(&ticks, utc(15, 1), Horizon::Minutes(5)).unwrap();
+    assert!((r - 0.01).abs() < 1e-9);
+    let r = intraday_return(&ticks, utc(15, 1), Horizon::Hours(1)).unwrap();
+    assert!((r - 0.1).abs() < 1e-9);
+    assert_eq!(intraday_return(&ticks, utc(14, 0), Horizon::Hours(1)), None);
+    assert_eq!(intraday_return(&ticks, utc(16, 1), Horizon::Hours(1)), None);
+  }
+
+  #[test]
+  fn test_daily_return() {
+    let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+    let closes = vec![(day(2), 100.0), (day(3), 110.0), (day(4), 99.0)];
+    // Before the close of Jan 2 (21:00 UTC) the base is the missing Jan 1 session.
+    assert_eq!(daily_return(&closes, utc(15, 0), 1), None);
+    let r = daily_return(&closes, utc(22, 0), 1).unwrap();
+    assert!((r - 0.1).abs() < 1e-9);
+    let r = daily_return(&closes, utc(22, 0), 2).unwrap();
+    assert!((r + 0.01).abs() < 1e-9);
+    assert_eq!(daily_return(&closes, utc(22, 0), 3), None);
+  }
+
+  #[test]
+  fn test_return_stats() {
+    assert_eq!(ReturnStats::from_returns(&[]), None);
+    let stats = ReturnStats::from_returns(&[0.02, -0.01, 0.03, 0.0]).unwrap();
+    assert_eq!(stats.count, 4);
+    assert!((stats.mean - 0.01).abs() < 1e-12);
+    assert!((stats.median - 0.01).abs() < 1e-12);
+    assert!((stats.hit_rate - 0.5).abs() < 1e-12);
+    assert_eq!(ReturnStats::from_returns(&[0.01]).unwrap().stddev, 0.0);
+  }
+
+  #[test]
+  fn test_write_csv() {
+    let row = EventReturn {
+      symbol: "AAPL".to_string(),
+      article: "abc".to_string(),
+      published: utc(15, 0),
+      label: SentimentLabel::Bullish,
+      relevance: 0.8,
+      sentiment: 0.4,
+      horizon: Horizon::Minutes(5),
+      forward_return: 0.01,
+    };
+    let quoted = EventReturn {
+      symbol: "BRK,B".to_string(),
+      ..row.clone()
+    };
+    let mut out = Vec::new();
+    write_csv(&mut out, &[row, quoted]).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+      lines[0],
+      "symbol,article,published,label,relevance_bucket,relevance,sentiment,horizon,return"
+    );
+    assert_eq!(
+      lines[1],
+      "AAPL,abc,2024-01-02T15:00:00+00:00,Bullish,0.75-1.00,0.8,0.4,5m,0.01"
+    );
+    assert!(lines[2].starts_with("\"BRK,B\",abc,"));
+  }
+}
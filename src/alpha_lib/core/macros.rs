@@ -32,11 +32,14 @@
 #[derive(PartialEq, Debug, Clone, Copy, Eq, Hash)]
 pub enum FuncType {
   TsIntra,
+  TsIntraFull,
   TsDaily,
+  TsDailyFull,
   Overview,
   SymSearch,
   TopQuery,
   NewsQuery,
+  MarketStatus,
 }
 
 /// `create_url!` is a macro used for constructing request URLs to various endpoints of the
@@ -51,8 +54,11 @@ pub enum FuncType {
 ///
 /// `TsIntraExt`: Constructs a URL for the TIME_SERIES_INTRADAY_EXTENDED endpoint.
 /// `TsDaily`: Constructs a URL for the TIME_SERIES_DAILY endpoint.
+/// `TsIntraFull`, `TsDailyFull` and `CryptoIntraDayFull`: The same endpoints with
+/// `outputsize=full`, used for the first load of a symbol.
 /// `Overview`: Constructs a URL for the OVERVIEW endpoint.
 /// `SymSearch`: Constructs a URL for the SYMBOL_SEARCH endpoint.
+/// `MarketStatus`: Constructs a URL for the MARKET_STATUS endpoint. The symbol is ignored.
 ///
 /// # Example
 ///
@@ -72,9 +78,15 @@ macro_rules! create_url {
     (FuncType::TsIntra,$string1:expr, $string2:expr) =>{
         format!("https://www.alphavantage.co/query?function=TIME_SERIES_INTRADAY&datatype=csv&symbol={}&interval=1min&apikey={}",$string1,$string2)
     };
+    (FuncType::TsIntraFull,$string1:expr, $string2:expr) =>{
+        format!("https://www.alphavantage.co/query?function=TIME_SERIES_INTRADAY&datatype=csv&symbol={}&interval=1min&outputsize=full&apikey={}",$string1,$string2)
+    };
     (FuncType::TsDaily,$string1:expr, $string2:expr) =>{
         format!("https://www.alphavantage.co/query?function=TIME_SERIES_DAILY&datatype=json&symbol={}&apikey={}",$string1,$string2)
     };
+    (FuncType::TsDailyFull,$string1:expr, $string2:expr) =>{
+        format!("https://www.alphavantage.co/query?function=TIME_SERIES_DAILY&datatype=json&symbol={}&outputsize=full&apikey={}",$string1,$string2)
+    };
     (FuncType::Overview,$string1:expr, $string2:expr) =>{
         format!("https://www.alphavantage.co/query?function=OVERVIEW&symbol={}&apikey={}",$string1,$string2)
     };
@@ -87,9 +99,15 @@ macro_rules! create_url {
     (FuncType::NewsQuery,$string1:expr, $string2:expr) =>{
         format!("https://www.alphavantage.co/query?function=NEWS_SENTIMENT&tickers={}&apikey={}",$string1,$string2)
     };
+    (FuncType::MarketStatus,$string1:expr, $string2:expr) =>{
+        format!("https://www.alphavantage.co/query?function=MARKET_STATUS&apikey={}",$string2)
+    };
     (FuncType::CryptoIntraDay,$string1:expr, $string2:expr) =>{
         format!("https://www.alphavantage.co/query?function=CRYPTO_INTRADAY&symbol={}&market=USD&interval=1min&apikey={}&datatype=csv",$string1,$string2)
     };
+    (FuncType::CryptoIntraDayFull,$string1:expr, $string2:expr) =>{
+        format!("https://www.alphavantage.co/query?function=CRYPTO_INTRADAY&symbol={}&market=USD&interval=1min&outputsize=full&apikey={}&datatype=csv",$string1,$string2)
+    };
     ($other:expr,$string1:expr, $string2:expr) =>{
         format!("Unknown function type received {:?}",$other)
     };
@@ -156,4 +174,31 @@ mod test {
     let url = create_url!(FuncType::CryptoIntraDay, "BTC", "12345678");
     assert_eq!(url,"https://www.alphavantage.co/query?function=CRYPTO_INTRADAY&symbol=BTC&market=USD&interval=1min&apikey=12345678&datatype=csv");
   }
+
+  #[test]
+  fn t_11() {
+    let url = create_url!(FuncType::MarketStatus, " ", "12345678");
+    assert_eq!(
+      url,
+      "https://www.alphavantage.co/query?function=MARKET_STATUS&apikey=12345678"
+    );
+  }
+
+  #[test]
+  fn t_12() {
+    let url = create_url!(FuncType::TsIntraFull, "AAPL", "12345678");
+    assert_eq!(url, "https://www.alphavantage.co/query?function=TIME_SERIES_INTRADAY&datatype=csv&symbol=AAPL&interval=1min&outputsize=full&apikey=12345678");
+  }
+
+  #[test]
+  fn t_13() {
+    let url = create_url!(FuncType::TsDailyFull, "AAPL", "12345678");
+    assert_eq!(url, "https://www.alphavantage.co/query?function=TIME_SERIES_DAILY&datatype=json&symbol=AAPL&outputsize=full&apikey=12345678");
+  }
+
+  #[test]
+  fn t_14() {
+    let url = create_url!(FuncType::CryptoIntraDayFull, "BTC", "12345678");
+    assert_eq!(url,"https://www.alphavantage.co/query?function=CRYPTO_INTRADAY&symbol=BTC&market=USD&interval=1min&outputsize=full&apikey=12345678&datatype=csv");
+  }
 }
@@ -0,0 +1,302 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+/// Zone of the exchange local timestamps returned by TIME_SERIES_INTRADAY, TOP_GAINERS_LOSERS and
+/// NEWS_SENTIMENT.
+pub const ALPHA_VANTAGE_TZ: Tz = Tz::US__Eastern;
+/// Zone of the timestamps returned by CRYPTO_INTRADAY.
+pub const CRYPTO_TZ: Tz = Tz::UTC;
+
+/// Maps a normalized region (see `normalize_alpha_region`) or a MARKET_STATUS region name to the
+/// IANA time zone of its primary exchange.
+///
+/// # Arguments
+///
+/// * `region` - The region as stored in the `symbols` table or as returned by MARKET_STATUS.
+///
+/// # Returns
+///
+/// * `Some(Tz)` for known regions, `None` otherwise.
+///
+/// # Example
+///
+/// ```ignore
+/// assert_eq!(region_timezone("USA"), Some(chrono_tz::America::New_York));
+/// ```
+pub fn region_timezone(region: &str) -> Option<Tz> {
+  let tz = match region {
+    "USA" | "United States" => Tz::America__New_York,
+    "UK" | "United Kingdom" => Tz::Europe__London,
+    "Frank" | "Frankfurt" | "XETRA" | "Germany" => Tz::Europe__Berlin,
+    "TOR" | "Toronto" | "Toronto Venture" | "Canada" => Tz::America__Toronto,
+    "Bomb" | "India/Bombay" | "India" => Tz::Asia__Kolkata,
+    "SaoP" | "Brazil/Sao Paolo" | "Brazil" => Tz::America__Sao_Paulo,
+    "Amsterdam" => Tz::Europe__Amsterdam,
+    "Paris/Brussels/Lisbon" | "France" => Tz::Europe__Paris,
+    "Spain" => Tz::Europe__Madrid,
+    "Portugal" => Tz::Europe__Lisbon,
+    "Shanghai" | "Shenzhen" | "Mainland China" => Tz::Asia__Shanghai,
+    "Hong Kong" => Tz::Asia__Hong_Kong,
+    "Tokyo" | "Japan" => Tz::Asia__Tokyo,
+    "Mexico" => Tz::America__Mexico_City,
+    "South Africa" => Tz::Africa__Johannesburg,
+    _ => return None,
+  };
+  Some(tz)
+}
+
+/// Parses the offset style time zones returned by SYMBOL_SEARCH ("UTC-04", "UTC+01", "UTC+05.5")
+/// into a `Tz`.
+///
+/// Whole hour offsets are mapped onto the `Etc/GMT` zones, whose signs are inverted by
+/// convention, so "UTC-04" becomes "Etc/GMT+4". Plain IANA names are accepted as well.
+pub fn parse_symbol_timezone(timezone: &str) -> Option<Tz> {
+  let timezone = timezone.trim();
+  if let Ok(tz) = Tz::from_str(timezone) {
+    return Some(tz);
+  }
+  let offset = timezone.strip_prefix("UTC")?;
+  if offset.is_empty() {
+    return Some(Tz::UTC);
+  }
+  match offset {
+    "+05.5" | "+5.5" | "+05:30" => return Some(Tz::Asia__Kolkata),
+    _ => {}
+  }
+  let hours = offset.parse::<i32>().ok()?;
+  if hours == 0 {
+    return Some(Tz::UTC);
+  }
+  Tz::from_str(&format!("Etc/GMT{:+}", -hours)).ok()
+}
+
+/// Resolves the time zone a symbol trades in, preferring the exchange zone of its region (which
+/// follows daylight saving time) over the fixed offset stored with the symbol.
+pub fn symbol_timezone(region: &str, timezone: &str) -> Tz {
+  region_timezone(region)
+    .or_else(|| parse_symbol_timezone(timezone))
+    .unwrap_or(Tz::UTC)
+}
+
+/// Converts a wall clock time in `tz` to UTC.
+///
+/// Ambiguous times in the autumn fold resolve to the earlier instant. Times that do not exist
+/// because they fall in the spring forward gap return `None`.
+///
+/// # Example
+///
+/// ```ignore
+/// let local = NaiveDateTime::parse_from_str("2024-03-08 16:00:00", "%Y-%m-%d %H:%M:%S")?;
+/// let utc = to_utc(local, ALPHA_VANTAGE_TZ); // 2024-03-08 21:00:00 UTC
+/// ```
+pub fn to_utc(local: NaiveDateTime, tz: Tz) -> Option<DateTime<Utc>> {
+  tz.from_local_datetime(&local)
+    .earliest()
+    .map(|t| t.with_timezone(&Utc))
+}
+
+/// Returns true if `date` is a weekday that is not an exchange holiday for `region`.
+///
+/// Only the US exchanges have a holiday calendar; every other region is treated as open on
+/// weekdays.
+pub fn is_trading_day(region: &str, date: NaiveDate) -> bool {
+  if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+    return false;
+  }
+  match region {
+    "USA" | "United States" => !us_market_holidays(date.year()).contains(&date),
+    _ => true,
+  }
+}
+
+/// Computes the NYSE/NASDAQ full day holidays for a given year.
+///
+/// Holidays falling on a Sunday are observed the following Monday, and those falling on a
+/// Saturday the preceding Friday. New Year's Day is the exception: when it falls on a Saturday
+/// the exchanges stay open on the Friday before.
+///
+/// # Example
+///
+/// ```ignore
+/// let holidays = us_market_holidays(2024);
+/// assert!(holidays.contains(&NaiveDate::from_ymd_opt(2024, 3, 29).unwrap())); // Good Friday
+/// ```
+pub fn us_market_holidays(year: i32) -> Vec<NaiveDate> {
+  let mut holidays = Vec::with_capacity(10);
+
+  let new_year = ymd(year, 1, 1);
+  if new_year.weekday() != Weekday::Sat {
+    holidays.push(observed(new_year));
+  }
+  holidays.push(nth_weekday(year, 1, Weekday::Mon, 3)); // Martin Luther King Jr. Day
+  holidays.push(nth_weekday(year, 2, Weekday::Mon, 3)); // Washington's Birthday
+  holidays.push(easter_sunday(year) - Duration::days(2)); // Good Friday
+  holidays.push(last_weekday(year, 5, Weekday::Mon)); // Memorial Day
+  if year >= 2022 {
+    holidays.push(observed(ymd(year, 6, 19))); // Juneteenth
+  }
+  holidays.push(observed(ymd(year, 7, 4)));
+  holidays.push(nth_weekday(year, 9, Weekday::Mon, 1)); // Labor Day
+  holidays.push(nth_weekday(year, 11, Weekday::Thu, 4)); // Thanksgiving
+  holidays.push(observed(ymd(year, 12, 25)));
+
+  holidays
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+  NaiveDate::from_ymd_opt(year, month, day).expect("valid calendar date")
+}
+
+fn observed(date: NaiveDate) -> NaiveDate {
+  match date.weekday() {
+    Weekday::Sat => date - Duration::days(1),
+    Weekday::Sun => date + Duration::days(1),
+    _ => date,
+  }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+  NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("valid weekday of month")
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+  let first_of_next = if month == 12 {
+    ymd(year + 1, 1, 1)
+  } else {
+    ymd(year, month + 1, 1)
+  };
+  let mut date = first_of_next - Duration::days(1);
+  while date.weekday() != weekday {
+    date -= Duration::days(1);
+  }
+  date
+}
+
+/// Anonymous Gregorian algorithm (Meeus/Jones/Butcher).
+fn easter_sunday(year: i32) -> NaiveDate {
+  let a = year % 19;
+  let b = year / 100;
+  let c = year % 100;
+  let d = b / 4;
+  let e = b % 4;
+  let f = (b + 8) / 25;
+  let g = (b - f + 1) / 3;
+  let h = (19 * a + b - d - g + 15) % 30;
+  let i = c / 4;
+  let k = c % 4;
+  let l = (32 + 2 * e + 2 * i - h - k) % 7;
+  let m = (a + 11 * h + 22 * l) / 451;
+  let month = (h + l - 7 * m + 114) / 31;
+  let day = (h + l - 7 * m + 114) % 31 + 1;
+  ymd(year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_us_market_holidays_2024() {
+    let expected: Vec<NaiveDate> = [
+      (1, 1),
+      (1, 15),
+      (2, 19),
+      (3, 29),
+      (5, 27),
+      (6, 19),
+      (7, 4),
+      (9, 2),
+      (11, 28),
+      (12, 25),
+    ]
+    .iter()
+    .map(|(m, d)| ymd(2024, *m, *d))
+    .collect();
+    assert_eq!(us_market_holidays(2024), expected);
+  }
+
+  #[test]
+  fn test_us_market_holidays_observed() {
+    let holidays = us_market_holidays(2022);
+    // New Year's Day 2022 fell on a Saturday and was not observed
+    assert!(!holidays.contains(&ymd(2021, 12, 31)));
+    assert!(!holidays.contains(&ymd(2022, 1, 3)));
+    // Juneteenth fell on a Sunday
+    assert!(holidays.contains(&ymd(2022, 6, 20)));
+    // Christmas fell on a Sunday
+    assert!(holidays.contains(&ymd(2022, 12, 26)));
+  }
+
+  #[test]
+  fn test_is_trading_day() {
+    assert!(is_trading_day("USA", ymd(2024, 3, 28)));
+    assert!(!is_trading_day("USA", ymd(2024, 3, 29)));
+    assert!(!is_trading_day("USA", ymd(2024, 3, 30)));
+    assert!(is_trading_day("UK", ymd(2024, 7, 4)));
+  }
+
+  #[test]
+  fn test_parse_symbol_timezone() {
+    assert_eq!(parse_symbol_timezone("UTC-04"), Some(Tz::Etc__GMTPlus4));
+    assert_eq!(parse_symbol_timezone("UTC+01"), Some(Tz::Etc__GMTMinus1));
+    assert_eq!(parse_symbol_timezone("UTC+05.5"), Some(Tz::Asia__Kolkata));
+    assert_eq!(parse_symbol_timezone("UTC"), Some(Tz::UTC));
+    assert_eq!(parse_symbol_timezone("US/Eastern"), Some(Tz::US__Eastern));
+    assert_eq!(parse_symbol_timezone("garbage"), None);
+  }
+
+  #[test]
+  fn test_to_utc() {
+    let parse = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+
+    let winter = to_utc(parse("2024-03-08 16:00:00"), ALPHA_VANTAGE_TZ).unwrap();
+    assert_eq!(winter.naive_utc(), parse("2024-03-08 21:00:00"));
+    let summer = to_utc(parse("2024-03-11 16:00:00"), ALPHA_VANTAGE_TZ).unwrap();
+    assert_eq!(summer.naive_utc(), parse("2024-03-11 20:00:00"));
+
+    let fold = to_utc(parse("2024-11-03 01:30:00"), ALPHA_VANTAGE_TZ).unwrap();
+    assert_eq!(fold.naive_utc(), parse("2024-11-03 05:30:00"));
+    assert!(to_utc(parse("2024-03-10 02:30:00"), ALPHA_VANTAGE_TZ).is_none());
+
+    let crypto = to_utc(parse("2024-03-10 02:30:00"), CRYPTO_TZ).unwrap();
+    assert_eq!(crypto.naive_utc(), parse("2024-03-10 02:30:00"));
+  }
+
+  #[test]
+  fn test_symbol_timezone() {
+    assert_eq!(symbol_timezone("USA", "UTC-04"), Tz::America__New_York);
+    assert_eq!(symbol_timezone("Nowhere", "UTC-05"), Tz::Etc__GMTPlus5);
+    assert_eq!(symbol_timezone("Nowhere", ""), Tz::UTC);
+  }
+}
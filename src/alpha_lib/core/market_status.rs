@@ -0,0 +1,290 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+// MARKET_STATUS based on https://www.alphavantage.co/query?function=MARKET_STATUS&apikey=demo
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+use crate::{
+  alpha_lib::core::{
+    alpha_funcs::normalize_alpha_region,
+    market_calendar::{is_trading_day, symbol_timezone},
+  },
+  db_models::Symbol,
+};
+
+/// Sessions older than this are not walked day by day; the symbol is simply reloaded.
+const MAX_LOOKBACK_DAYS: i64 = 14;
+const OPEN: &str = "open";
+const EQUITY: &str = "Equity";
+const CRYPTO: &str = "Crypto";
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct MarketStatusRoot {
+  pub endpoint: String,
+  pub markets: Vec<Market>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct Market {
+  pub market_type: String,
+  pub region: String,
+  pub primary_exchanges: String,
+  pub local_open: String,
+  pub local_close: String,
+  pub current_status: String,
+  pub notes: String,
+}
+
+/// The trading hours of a symbol, resolved from the `marketopen`, `marketclose` and `timezone`
+/// columns of the `symbols` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradingSession {
+  pub region: String,
+  pub open: NaiveTime,
+  pub close: NaiveTime,
+  pub tz: Tz,
+  pub always_open: bool,
+}
+
+impl TradingSession {
+  pub fn new(region: &str, sec_type: &str, open: NaiveTime, close: NaiveTime, tz: &str) -> Self {
+    Self {
+      region: region.to_string(),
+      open,
+      close,
+      tz: symbol_timezone(region, tz),
+      always_open: sec_type == CRYPTO || close <= open,
+    }
+  }
+
+  pub fn from_symbol(sym: &Symbol) -> Self {
+    Self::new(
+      &sym.region,
+      &sym.sec_type,
+      sym.marketopen,
+      sym.marketclose,
+      &sym.timezone,
+    )
+  }
+
+  /// Returns true if any session of this market overlaps the interval `(from, to]`.
+  pub fn traded_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+    if self.always_open {
+      return true;
+    }
+    let mut day = from.with_timezone(&self.tz).date_naive();
+    let last_day = to.with_timezone(&self.tz).date_naive();
+    while day <= last_day {
+      if is_trading_day(&self.region, day) {
+        let start = self
+          .tz
+          .from_local_datetime(&day.and_time(self.open))
+          .earliest();
+        let end = self
+          .tz
+          .from_local_datetime(&day.and_time(self.close))
+          .latest();
+        if let (Some(start), Some(end)) = (start, end) {
+          if start < to && end > from {
+            return true;
+          }
+        }
+      }
+      day = match day.succ_opt() {
+        Some(next) => next,
+        None => break,
+      };
+    }
+    false
+  }
+}
+
+/// Decides which symbols are worth a request by combining the live MARKET_STATUS response with
+/// the local holiday calendar and the time of the previous successful run.
+///
+/// # Example
+///
+/// ```ignore
+/// let schedule = MarketSchedule::new(get_market_status().ok().as_ref(), last_run, Utc::now());
+/// for sym in symbols {
+///   if !schedule.has_new_data(&TradingSession::from_symbol(&sym)) {
+///     continue;
+///   }
+///   // hit the API
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarketSchedule {
+  open_regions: HashSet<String>,
+  last_run: Option<DateTime<Utc>>,
+  now: DateTime<Utc>,
+}
+
+impl MarketSchedule {
+  pub fn new(
+    status: Option<&MarketStatusRoot>,
+    last_run: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+  ) -> Self {
+    let open_regions = status
+      .map(|root| {
+        root
+          .markets
+          .iter()
+          .filter(|m| m.market_type == EQUITY && m.current_status.eq_ignore_ascii_case(OPEN))
+          .map(|m| status_region(&m.region))
+          .collect()
+      })
+      .unwrap_or_default();
+
+    Self {
+      open_regions,
+      last_run,
+      now,
+    }
+  }
+
+  /// Returns true if MARKET_STATUS reported the region as open.
+  pub fn is_open(&self, region: &str) -> bool {
+    self.open_regions.contains(region)
+  }
+
+  /// Returns true if the market is open now or has traded since the last run.
+  pub fn has_new_data(&self, session: &TradingSession) -> bool {
+    let last_run = match self.last_run {
+      Some(last_run) => last_run,
+      None => return true,
+    };
+    if session.always_open || self.is_open(&session.region) {
+      return true;
+    }
+    if self.now - last_run > Duration::days(MAX_LOOKBACK_DAYS) {
+      return true;
+    }
+    session.traded_between(last_run, self.now)
+  }
+}
+
+/// Maps the MARKET_STATUS region names onto the region codes used in the `symbols` table.
+fn status_region(region: &str) -> String {
+  match region {
+    "Germany" => "Frank".to_string(),
+    "Canada" => "TOR".to_string(),
+    "India" => "Bomb".to_string(),
+    "Brazil" => "SaoP".to_string(),
+    _ => normalize_alpha_region(region),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn us_session() -> TradingSession {
+    TradingSession::new(
+      "USA",
+      "Eqty",
+      NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+      NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+      "UTC-04",
+    )
+  }
+
+  fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+  }
+
+  #[test]
+  fn test_weekend_has_no_new_data() {
+    // Friday 2024-03-08 after the close through Sunday
+    let schedule = MarketSchedule::new(None, Some(utc(2024, 3, 8, 22, 0)), utc(2024, 3, 10, 18, 0));
+    assert!(!schedule.has_new_data(&us_session()));
+  }
+
+  #[test]
+  fn test_holiday_has_no_new_data() {
+    // Thursday close through Good Friday evening 2024
+    let schedule =
+      MarketSchedule::new(None, Some(utc(2024, 3, 28, 21, 0)), utc(2024, 3, 29, 23, 0));
+    assert!(!schedule.has_new_data(&us_session()));
+  }
+
+  #[test]
+  fn test_session_since_last_run() {
+    // Sunday through Monday midday
+    let schedule =
+      MarketSchedule::new(None, Some(utc(2024, 3, 10, 12, 0)), utc(2024, 3, 11, 16, 0));
+    assert!(schedule.has_new_data(&us_session()));
+  }
+
+  #[test]
+  fn test_first_run_and_crypto() {
+    let schedule = MarketSchedule::new(None, None, utc(2024, 3, 10, 18, 0));
+    assert!(schedule.has_new_data(&us_session()));
+
+    let crypto = TradingSession::new(
+      "USA",
+      "Crypto",
+      NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+      NaiveTime::from_hms_opt(23, 59, 0).unwrap(),
+      "UTC-04",
+    );
+    let schedule = MarketSchedule::new(None, Some(utc(2024, 3, 9, 12, 0)), utc(2024, 3, 10, 18, 0));
+    assert!(schedule.has_new_data(&crypto));
+  }
+
+  #[test]
+  fn test_market_status_open_region() {
+    let root: MarketStatusRoot = serde_json::from_str(
+      r#"{"endpoint": "Global Market Open & Close Status",
+          "markets": [
+            {"market_type": "Equity", "region": "United States",
+             "primary_exchanges": "NASDAQ, NYSE, AMEX, BATS", "local_open": "09:30",
+             "local_close": "16:15", "current_status": "open", "notes": ""},
+            {"market_type": "Equity", "region": "Germany", "primary_exchanges": "XETRA, Berlin",
+             "local_open": "08:00", "local_close": "20:00", "current_status": "closed",
+             "notes": ""}
+          ]}"#,
+    )
+    .unwrap();
+    let schedule = MarketSchedule::new(
+      Some(&root),
+      Some(utc(2024, 3, 9, 12, 0)),
+      utc(2024, 3, 9, 13, 0),
+    );
+    assert!(schedule.is_open("USA"));
+    assert!(!schedule.is_open("Frank"));
+    assert!(schedule.has_new_data(&us_session()));
+  }
+}
@@ -0,0 +1,199 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use std::collections::HashSet;
+
+use reqwest::Url;
+use ring::digest::{digest, SHA256};
+
+/// Titles with at least this share of common words are treated as the same story.
+pub const TITLE_SIMILARITY_THRESHOLD: f64 = 0.8;
+/// Titles shorter than this many words must match exactly to be treated as the same story.
+const MIN_SIMILAR_WORDS: usize = 4;
+
+const TRACKING_PARAMS: [&str; 14] = [
+  "fbclid",
+  "gclid",
+  "dclid",
+  "msclkid",
+  "mc_cid",
+  "mc_eid",
+  "cmpid",
+  "ncid",
+  "yptr",
+  "ref",
+  "guccounter",
+  "guce_referrer",
+  "soc_src",
+  "soc_trk",
+];
+
+/// Normalizes an article URL so that the same story reached through different links compares
+/// equal.
+///
+/// The scheme is forced to https, the host is lower cased and stripped of "www.", fragments,
+/// trailing slashes and tracking parameters (`utm_*`, `fbclid`, ...) are removed and the remaining
+/// query parameters are sorted. Strings that do not parse as a URL are only trimmed.
+///
+/// # Example
+///
+/// ```ignore
+/// assert_eq!(
+///   canonical_url("http://WWW.Example.com/story/?utm_source=x&id=7#top"),
+///   "https://example.com/story?id=7"
+/// );
+/// ```
+pub fn canonical_url(url: &str) -> String {
+  let parsed = match Url::parse(url.trim()) {
+    Ok(parsed) if parsed.has_host() => parsed,
+    _ => return url.trim().to_string(),
+  };
+
+  let host = parsed.host_str().unwrap_or_default().to_lowercase();
+  let host = host.strip_prefix("www.").unwrap_or(&host);
+  let path = parsed.path().trim_end_matches('/');
+
+  let mut query: Vec<(String, String)> = parsed
+    .query_pairs()
+    .filter(|(key, _)| !is_tracking_param(key))
+    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+    .collect();
+  query.sort();
+
+  let mut canonical = format!("https://{}{}", host, path);
+  if let Some(port) = parsed.port() {
+    canonical = format!("https://{}:{}{}", host, port, path);
+  }
+  if !query.is_empty() {
+    let pairs: Vec<String> = query
+      .iter()
+      .map(|(key, value)| match value.is_empty() {
+        true => key.clone(),
+        false => format!("{}={}", key, value),
+      })
+      .collect();
+    canonical.push('?');
+    canonical.push_str(&pairs.join("&"));
+  }
+  canonical
+}
+
+fn is_tracking_param(key: &str) -> bool {
+  let key = key.to_lowercase();
+  key.starts_with("utm_") || TRACKING_PARAMS.contains(&key.as_str())
+}
+
+/// The id of an article: the hex encoded SHA256 digest of its canonical URL.
+pub fn article_id(canonical_url: &str) -> String {
+  digest(&SHA256, canonical_url.as_bytes())
+    .as_ref()
+    .iter()
+    .map(|b| format!("{:02x}", b))
+    .collect()
+}
+
+/// Splits a title into its lower cased words, ignoring punctuation.
+pub fn title_words(title: &str) -> HashSet<String> {
+  title
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|w| !w.is_empty())
+    .map(str::to_lowercase)
+    .collect()
+}
+
+/// The Jaccard similarity of the words of two titles, between 0.0 and 1.0.
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+  let (a, b) = (title_words(a), title_words(b));
+  if a.is_empty() && b.is_empty() {
+    return 1.0;
+  }
+  let common = a.intersection(&b).count();
+  common as f64 / (a.len() + b.len() - common) as f64
+}
+
+/// Returns true if two titles most likely belong to the same story, e.g. a syndicated copy of
+/// an article with a reworded headline.
+pub fn is_near_duplicate(a: &str, b: &str) -> bool {
+  let (words_a, words_b) = (title_words(a), title_words(b));
+  if words_a.is_empty() || words_b.is_empty() {
+    return false;
+  }
+  if words_a.len().min(words_b.len()) < MIN_SIMILAR_WORDS {
+    return words_a == words_b;
+  }
+  title_similarity(a, b) >= TITLE_SIMILARITY_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_canonical_url() {
+    assert_eq!(
+      canonical_url("http://WWW.Example.com/story/?utm_source=x&id=7&fbclid=abc#top"),
+      "https://example.com/story?id=7"
+    );
+    assert_eq!(
+      canonical_url("https://example.com/a?b=2&a=1"),
+      canonical_url("https://www.example.com/a/?a=1&b=2&utm_medium=rss")
+    );
+    assert_eq!(canonical_url(" not a url "), "not a url");
+  }
+
+  #[test]
+  fn test_article_id() {
+    let id = article_id("https://example.com/story");
+    assert_eq!(id.len(), 64);
+    assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_eq!(
+      id,
+      article_id(&canonical_url("https://www.example.com/story/"))
+    );
+  }
+
+  #[test]
+  fn test_near_duplicate_titles() {
+    assert!(is_near_duplicate(
+      "Apple Beats Earnings Estimates as iPhone Sales Surge",
+      "Apple beats earnings estimates as iPhone sales surge!"
+    ));
+    assert!(is_near_duplicate(
+      "Apple beats earnings estimates as iPhone sales surge in China",
+      "Apple beats earnings estimates as iPhone sales surge"
+    ));
+    assert!(!is_near_duplicate(
+      "Apple beats earnings estimates",
+      "Microsoft misses earnings estimates"
+    ));
+    assert!(!is_near_duplicate("Stocks rally", "Stocks fall"));
+    assert!(is_near_duplicate("Stocks rally", "stocks, rally"));
+    assert!(!is_near_duplicate("", ""));
+  }
+}
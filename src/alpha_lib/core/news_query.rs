@@ -0,0 +1,265 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+  alpha_lib::core::market_calendar::ALPHA_VANTAGE_TZ, security_types::sec_types::SecurityType,
+};
+
+/// The largest `limit` NEWS_SENTIMENT accepts. Without a limit only 50 items are returned.
+pub const MAX_NEWS_LIMIT: u32 = 1000;
+const TIME_FORMAT: &str = "%Y%m%dT%H%M";
+const CRYPTO_PREFIX: &str = "CRYPTO:";
+const FOREX_PREFIX: &str = "FOREX:";
+
+/// The topics accepted by the `topics=` parameter of NEWS_SENTIMENT, paired with the name the
+/// topic has in the `topics` section of each article (and so in `topicrefs`).
+pub const NEWS_TOPICS: [(&str, &str); 15] = [
+  ("blockchain", "Blockchain"),
+  ("earnings", "Earnings"),
+  ("ipo", "IPO"),
+  ("mergers_and_acquisitions", "Mergers & Acquisitions"),
+  ("financial_markets", "Financial Markets"),
+  ("economy_fiscal", "Economy - Fiscal"),
+  ("economy_monetary", "Economy - Monetary"),
+  ("economy_macro", "Economy - Macro"),
+  ("energy_transportation", "Energy & Transportation"),
+  ("finance", "Finance"),
+  ("life_sciences", "Life Sciences"),
+  ("manufacturing", "Manufacturing"),
+  ("real_estate", "Real Estate & Construction"),
+  ("retail_wholesale", "Retail & Wholesale"),
+  ("technology", "Technology"),
+];
+
+/// Maps a topic name as stored in `topicrefs`, or an API topic, to the value of the `topics=`
+/// parameter.
+///
+/// # Example
+///
+/// ```ignore
+/// assert_eq!(topic_query_param("Mergers & Acquisitions"), Some("mergers_and_acquisitions"));
+/// ```
+pub fn topic_query_param(topic: &str) -> Option<&'static str> {
+  NEWS_TOPICS
+    .iter()
+    .find(|(param, name)| *name == topic || *param == topic)
+    .map(|(param, _)| *param)
+}
+
+/// Returns the ticker NEWS_SENTIMENT uses for a symbol: digital currencies and currencies are
+/// prefixed with "CRYPTO:" and "FOREX:", everything else is the plain symbol.
+pub fn news_ticker(sec_type: SecurityType, symbol: &str) -> String {
+  match sec_type {
+    SecurityType::Crypto => format!("{}{}", CRYPTO_PREFIX, symbol),
+    SecurityType::FX => format!("{}{}", FOREX_PREFIX, symbol),
+    _ => symbol.to_string(),
+  }
+}
+
+/// Splits a `ticker_sentiment` ticker into its security type and symbol.
+///
+/// # Example
+///
+/// ```ignore
+/// assert_eq!(parse_news_ticker("CRYPTO:BTC"), (Some(SecurityType::Crypto), "BTC"));
+/// assert_eq!(parse_news_ticker("AAPL"), (None, "AAPL"));
+/// ```
+pub fn parse_news_ticker(ticker: &str) -> (Option<SecurityType>, &str) {
+  if let Some(symbol) = ticker.strip_prefix(CRYPTO_PREFIX) {
+    (Some(SecurityType::Crypto), symbol)
+  } else if let Some(symbol) = ticker.strip_prefix(FOREX_PREFIX) {
+    (Some(SecurityType::FX), symbol)
+  } else {
+    (None, ticker)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+  Latest,
+  Earliest,
+  Relevance,
+}
+
+impl fmt::Display for SortOrder {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let s = match self {
+      SortOrder::Latest => "LATEST",
+      SortOrder::Earliest => "EARLIEST",
+      SortOrder::Relevance => "RELEVANCE",
+    };
+    write!(f, "{}", s)
+  }
+}
+
+impl FromStr for SortOrder {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_uppercase().as_str() {
+      "LATEST" => Ok(SortOrder::Latest),
+      "EARLIEST" => Ok(SortOrder::Earliest),
+      "RELEVANCE" => Ok(SortOrder::Relevance),
+      _ => Err(format!("Unknown sort order {}", s)),
+    }
+  }
+}
+
+/// The filters of a NEWS_SENTIMENT request.
+///
+/// Unset fields are left out of the URL so Alpha Vantage applies its own defaults. Times are
+/// converted from UTC to the US/Eastern wall clock the endpoint expects.
+///
+/// # Example
+///
+/// ```ignore
+/// let query = NewsQuery {
+///   tickers: vec!["AAPL".to_string()],
+///   sort: Some(SortOrder::Earliest),
+///   limit: Some(MAX_NEWS_LIMIT),
+///   ..Default::default()
+/// };
+/// let url = query.url(&api_key);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewsQuery {
+  pub tickers: Vec<String>,
+  pub topics: Vec<String>,
+  pub time_from: Option<DateTime<Utc>>,
+  pub time_to: Option<DateTime<Utc>>,
+  pub sort: Option<SortOrder>,
+  pub limit: Option<u32>,
+}
+
+impl NewsQuery {
+  pub fn url(&self, api_key: &str) -> String {
+    let mut url = "https://www.alphavantage.co/query?function=NEWS_SENTIMENT".to_string();
+    if !self.tickers.is_empty() {
+      url.push_str(&format!("&tickers={}", self.tickers.join(",")));
+    }
+    if !self.topics.is_empty() {
+      url.push_str(&format!("&topics={}", self.topics.join(",")));
+    }
+    if let Some(time_from) = self.time_from {
+      url.push_str(&format!("&time_from={}", format_time(time_from)));
+    }
+    if let Some(time_to) = self.time_to {
+      url.push_str(&format!("&time_to={}", format_time(time_to)));
+    }
+    if let Some(sort) = self.sort {
+      url.push_str(&format!("&sort={}", sort));
+    }
+    if let Some(limit) = self.limit {
+      url.push_str(&format!("&limit={}", limit.min(MAX_NEWS_LIMIT)));
+    }
+    url.push_str(&format!("&apikey={}", api_key));
+    url
+  }
+}
+
+fn format_time(t: DateTime<Utc>) -> String {
+  t.with_timezone(&ALPHA_VANTAGE_TZ)
+    .format(TIME_FORMAT)
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  #[test]
+  fn test_default_url() {
+    let query = NewsQuery {
+      tickers: vec!["AAPL".to_string()],
+      ..Default::default()
+    };
+    assert_eq!(
+      query.url("12345678"),
+      "https://www.alphavantage.co/query?function=NEWS_SENTIMENT&tickers=AAPL&apikey=12345678"
+    );
+  }
+
+  #[test]
+  fn test_full_url() {
+    let query = NewsQuery {
+      tickers: vec!["AAPL".to_string(), "MSFT".to_string()],
+      topics: vec!["technology".to_string()],
+      time_from: Some(Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 45).unwrap()),
+      time_to: Some(Utc.with_ymd_and_hms(2024, 7, 2, 14, 30, 0).unwrap()),
+      sort: Some(SortOrder::Earliest),
+      limit: Some(5000),
+    };
+    assert_eq!(
+      query.url("12345678"),
+      "https://www.alphavantage.co/query?function=NEWS_SENTIMENT&tickers=AAPL,MSFT\
+       &topics=technology&time_from=20240102T0930&time_to=20240702T1030&sort=EARLIEST\
+       &limit=1000&apikey=12345678"
+    );
+  }
+
+  #[test]
+  fn test_sort_order() {
+    assert_eq!("relevance".parse::<SortOrder>(), Ok(SortOrder::Relevance));
+    assert_eq!(SortOrder::Latest.to_string(), "LATEST");
+    assert!("NEWEST".parse::<SortOrder>().is_err());
+  }
+
+  #[test]
+  fn test_news_ticker() {
+    assert_eq!(news_ticker(SecurityType::Crypto, "BTC"), "CRYPTO:BTC");
+    assert_eq!(news_ticker(SecurityType::FX, "USD"), "FOREX:USD");
+    assert_eq!(news_ticker(SecurityType::Equity, "AAPL"), "AAPL");
+
+    assert_eq!(
+      parse_news_ticker("CRYPTO:BTC"),
+      (Some(SecurityType::Crypto), "BTC")
+    );
+    assert_eq!(
+      parse_news_ticker("FOREX:EUR"),
+      (Some(SecurityType::FX), "EUR")
+    );
+    assert_eq!(parse_news_ticker("AAPL"), (None, "AAPL"));
+  }
+
+  #[test]
+  fn test_topic_query_param() {
+    assert_eq!(
+      topic_query_param("Mergers & Acquisitions"),
+      Some("mergers_and_acquisitions")
+    );
+    assert_eq!(topic_query_param("technology"), Some("technology"));
+    assert_eq!(topic_query_param("Astrology"), None);
+  }
+}
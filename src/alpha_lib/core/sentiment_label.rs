@@ -0,0 +1,274 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use std::{fmt, io::Write, str::FromStr};
+
+use diesel::{
+  deserialize::{self, FromSql, FromSqlRow},
+  expression::AsExpression,
+  pg::{Pg, PgValue},
+  serialize::{self, IsNull, Output, ToSql},
+};
+use serde::Serialize;
+
+use crate::schema::sql_types::SentimentLabel as SentimentLabelType;
+
+/// The sentiment labels of NEWS_SENTIMENT, from most bearish to most bullish. Stored as the
+/// `sentiment_label` enum of the database.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = SentimentLabelType)]
+pub enum SentimentLabel {
+  Bearish,
+  SomewhatBearish,
+  Neutral,
+  SomewhatBullish,
+  Bullish,
+}
+
+impl SentimentLabel {
+  pub const ALL: [SentimentLabel; 5] = [
+    SentimentLabel::Bearish,
+    SentimentLabel::SomewhatBearish,
+    SentimentLabel::Neutral,
+    SentimentLabel::SomewhatBullish,
+    SentimentLabel::Bullish,
+  ];
+
+  /// The label as Alpha Vantage spells it in the feed, e.g. "Somewhat-Bullish".
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SentimentLabel::Bearish => "Bearish",
+      SentimentLabel::SomewhatBearish => "Somewhat-Bearish",
+      SentimentLabel::Neutral => "Neutral",
+      SentimentLabel::SomewhatBullish => "Somewhat-Bullish",
+      SentimentLabel::Bullish => "Bullish",
+    }
+  }
+
+  /// 1 for bullish labels, -1 for bearish labels and 0 for neutral.
+  pub fn direction(&self) -> i8 {
+    match self {
+      SentimentLabel::Bearish | SentimentLabel::SomewhatBearish => -1,
+      SentimentLabel::Neutral => 0,
+      SentimentLabel::SomewhatBullish | SentimentLabel::Bullish => 1,
+    }
+  }
+}
+
+impl fmt::Display for SentimentLabel {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+/// Accepts the spellings seen in feeds and in the score definition: case is ignored and
+/// "Somewhat-Bullish", "Somewhat_Bullish" and "Somewhat Bullish" are the same label.
+impl FromStr for SentimentLabel {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let normalized: String = s
+      .trim()
+      .chars()
+      .filter(|c| !matches!(c, '-' | '_' | ' '))
+      .collect::<String>()
+      .to_lowercase();
+    match normalized.as_str() {
+      "bearish" => Ok(SentimentLabel::Bearish),
+      "somewhatbearish" => Ok(SentimentLabel::SomewhatBearish),
+      "neutral" => Ok(SentimentLabel::Neutral),
+      "somewhatbullish" => Ok(SentimentLabel::SomewhatBullish),
+      "bullish" => Ok(SentimentLabel::Bullish),
+      _ => Err(format!("Unknown sentiment label {}", s)),
+    }
+  }
+}
+
+impl ToSql<SentimentLabelType, Pg> for SentimentLabel {
+  fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+    out.write_all(self.as_str().as_bytes())?;
+    Ok(IsNull::No)
+  }
+}
+
+impl FromSql<SentimentLabelType, Pg> for SentimentLabel {
+  fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+    std::str::from_utf8(bytes.as_bytes())?
+      .parse::<SentimentLabel>()
+      .map_err(Into::into)
+  }
+}
+
+/// Score boundaries between the sentiment labels.
+///
+/// A score at or below `bearish` is Bearish, at or below `somewhat_bearish` Somewhat-Bearish,
+/// below `somewhat_bullish` Neutral, below `bullish` Somewhat-Bullish and Bullish otherwise. The
+/// default is the definition Alpha Vantage publishes with every feed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SentimentThresholds {
+  pub bearish: f64,
+  pub somewhat_bearish: f64,
+  pub somewhat_bullish: f64,
+  pub bullish: f64,
+}
+
+impl Default for SentimentThresholds {
+  fn default() -> Self {
+    SentimentThresholds {
+      bearish: -0.35,
+      somewhat_bearish: -0.15,
+      somewhat_bullish: 0.15,
+      bullish: 0.35,
+    }
+  }
+}
+
+impl SentimentThresholds {
+  /// Derives the label of a sentiment score.
+  pub fn label(&self, score: f64) -> SentimentLabel {
+    if score <= self.bearish {
+      SentimentLabel::Bearish
+    } else if score <= self.somewhat_bearish {
+      SentimentLabel::SomewhatBearish
+    } else if score < self.somewhat_bullish {
+      SentimentLabel::Neutral
+    } else if score < self.bullish {
+      SentimentLabel::SomewhatBullish
+    } else {
+      SentimentLabel::Bullish
+    }
+  }
+
+  /// Renders the thresholds in the format of the `sentiment_score_definition` of a feed.
+  pub fn definition(&self) -> String {
+    format!(
+      "x <= {0}: Bearish; {0} < x <= {1}: Somewhat-Bearish; {1} < x < {2}: Neutral; \
+       {2} <= x < {3}: Somewhat_Bullish; x >= {3}: Bullish",
+      self.bearish, self.somewhat_bearish, self.somewhat_bullish, self.bullish
+    )
+  }
+
+  /// Reads the thresholds from the `sentiment_score_definition` of a feed, e.g.
+  /// "x <= -0.35: Bearish; -0.35 < x <= -0.15: Somewhat-Bearish; -0.15 < x < 0.15: Neutral;
+  /// 0.15 <= x < 0.35: Somewhat_Bullish; x >= 0.35: Bullish".
+  ///
+  /// Returns `None` unless all four boundaries are found in increasing order.
+  pub fn parse_definition(definition: &str) -> Option<SentimentThresholds> {
+    let (mut bearish, mut somewhat_bearish, mut somewhat_bullish, mut bullish) =
+      (None, None, None, None);
+    for clause in definition.split(';') {
+      let (condition, label) = clause.rsplit_once(':')?;
+      let bounds: Vec<f64> = condition
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .filter_map(|n| n.parse::<f64>().ok())
+        .collect();
+      match label.parse::<SentimentLabel>().ok()? {
+        SentimentLabel::Bearish => bearish = bounds.last().copied(),
+        SentimentLabel::SomewhatBearish => somewhat_bearish = bounds.last().copied(),
+        SentimentLabel::Neutral => {}
+        SentimentLabel::SomewhatBullish => somewhat_bullish = bounds.first().copied(),
+        SentimentLabel::Bullish => bullish = bounds.first().copied(),
+      }
+    }
+    let thresholds = SentimentThresholds {
+      bearish: bearish?,
+      somewhat_bearish: somewhat_bearish?,
+      somewhat_bullish: somewhat_bullish?,
+      bullish: bullish?,
+    };
+    let ordered = thresholds.bearish < thresholds.somewhat_bearish
+      && thresholds.somewhat_bearish < thresholds.somewhat_bullish
+      && thresholds.somewhat_bullish < thresholds.bullish;
+    ordered.then_some(thresholds)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const DEFINITION: &str = "x <= -0.35: Bearish; -0.35 < x <= -0.15: Somewhat-Bearish; -0.15 < x \
+                            < 0.15: Neutral; 0.15 <= x < 0.35: Somewhat_Bullish; x >= 0.35: \
+                            Bullish";
+
+  #[test]
+  fn test_parse_label() {
+    assert_eq!(
+      "Somewhat-Bullish".parse::<SentimentLabel>(),
+      Ok(SentimentLabel::SomewhatBullish)
+    );
+    assert_eq!(
+      "somewhat_bearish".parse::<SentimentLabel>(),
+      Ok(SentimentLabel::SomewhatBearish)
+    );
+    assert_eq!(
+      " BULLISH ".parse::<SentimentLabel>(),
+      Ok(SentimentLabel::Bullish)
+    );
+    assert!("Mixed".parse::<SentimentLabel>().is_err());
+    for label in SentimentLabel::ALL {
+      assert_eq!(label.as_str().parse::<SentimentLabel>(), Ok(label));
+    }
+  }
+
+  #[test]
+  fn test_label_from_score() {
+    let thresholds = SentimentThresholds::default();
+    assert_eq!(thresholds.label(-0.35), SentimentLabel::Bearish);
+    assert_eq!(thresholds.label(-0.15), SentimentLabel::SomewhatBearish);
+    assert_eq!(thresholds.label(0.0), SentimentLabel::Neutral);
+    assert_eq!(thresholds.label(0.15), SentimentLabel::SomewhatBullish);
+    assert_eq!(thresholds.label(0.35), SentimentLabel::Bullish);
+    assert_eq!(SentimentLabel::SomewhatBearish.direction(), -1);
+  }
+
+  #[test]
+  fn test_parse_definition() {
+    assert_eq!(
+      SentimentThresholds::parse_definition(DEFINITION),
+      Some(SentimentThresholds::default())
+    );
+    let custom = "x <= -0.5: Bearish; -0.5 < x <= -0.2: Somewhat-Bearish; -0.2 < x < 0.2: \
+                  Neutral; 0.2 <= x < 0.5: Somewhat-Bullish; x >= 0.5: Bullish";
+    assert_eq!(
+      SentimentThresholds::parse_definition(custom).map(|t| t.bullish),
+      Some(0.5)
+    );
+    assert_eq!(SentimentThresholds::default().definition(), DEFINITION);
+    let thresholds = SentimentThresholds::parse_definition(custom).unwrap();
+    assert_eq!(
+      SentimentThresholds::parse_definition(&thresholds.definition()),
+      Some(thresholds)
+    );
+    assert_eq!(SentimentThresholds::parse_definition("x: Neutral"), None);
+    assert_eq!(SentimentThresholds::parse_definition(""), None);
+  }
+}
@@ -0,0 +1,71 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![allow(unexpected_cfgs)]
+#[cfg(not(tarpaulin_include))]
+use std::process;
+
+use alpha_vantage_rust::{
+  alpha_lib::misc_functions::get_exe_name,
+  dbfunctions::{
+    articles::backfill_article_identity,
+    base::establish_connection_or_exit,
+    process::{get_proc_id_or_insert, log_proc_end, log_proc_start},
+    sentiment_series::refresh_sentiment_series,
+  },
+};
+use chrono::DateTime;
+use dotenvy::dotenv;
+
+/// Fills the canonical urls of the articles stored before they were introduced and links their
+/// syndicated copies, then rebuilds the sentiment series so that each story counts once.
+fn main() {
+  dotenv().ok();
+  let conn = &mut establish_connection_or_exit();
+
+  let id_val = get_proc_id_or_insert(conn, &get_exe_name()).unwrap();
+  let pid = log_proc_start(conn, id_val).unwrap();
+
+  match backfill_article_identity(conn) {
+    Ok((updated, linked)) => println!("{} articles updated, {} linked as copies", updated, linked),
+    Err(err) => {
+      eprintln!("Cannot backfill the articles: {}", err);
+      _ = log_proc_end(conn, pid, 3).unwrap();
+      process::exit(1);
+    }
+  }
+  match refresh_sentiment_series(conn, Some(DateTime::UNIX_EPOCH)) {
+    Ok(buckets) => println!("{} sentiment buckets rebuilt", buckets),
+    Err(err) => {
+      eprintln!("Cannot refresh the sentiment series: {}", err);
+      _ = log_proc_end(conn, pid, 3).unwrap();
+      process::exit(1);
+    }
+  }
+  _ = log_proc_end(conn, pid, 2).unwrap();
+}
@@ -38,16 +38,19 @@ use std::{
 use alpha_vantage_rust::{
   alpha_lib::{
     alpha_io::{
-      base::{load_intraday, load_summary},
+      base::{get_market_schedule, load_intraday, load_summary},
       news_loader::{load_news, Params},
     },
+    core::market_status::TradingSession,
     misc_functions::get_exe_name,
   },
+  db_models::Symbol,
   dbfunctions::{
     author::get_authors,
     base::establish_connection_or_exit,
-    combined::get_sids_and_names_with_overview,
+    combined::get_symbols_with_overview,
     process::{get_proc_id_or_insert, log_proc_end, log_proc_start},
+    sentiment_series::refresh_sentiment_series,
     sources::get_sources,
     topic_refs::get_topics,
   },
@@ -62,8 +65,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
   let id_val = get_proc_id_or_insert(conn, &get_exe_name()).unwrap();
 
+  let schedule = get_market_schedule(conn, id_val)?;
   let pid = log_proc_start(conn, id_val).unwrap();
-  let results: Vec<(i64, String)> = get_sids_and_names_with_overview(conn).unwrap_or_else(|err| {
+  let results: Vec<Symbol> = get_symbols_with_overview(conn).unwrap_or_else(|err| {
     println!("Cannot load results from database {}", err);
     _ = log_proc_end(conn, pid, 3).unwrap();
     process::exit(1);
@@ -76,8 +80,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
   let authors = get_authors(conn)?;
   let sources = get_sources(conn)?;
 
-  for (sid, name) in results.iter() {
-    params.names_to_sid.insert(name.clone(), *sid);
+  for sym in results.iter() {
+    params.names_to_sid.insert(sym.symbol.clone(), sym.sid);
   }
 
   params.topics = topics.iter().map(|t| (t.name.clone(), t.id)).collect();
@@ -99,13 +103,20 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
       .progress_chars("##-"),
   );
 
-  for (s_id, symb) in results {
+  for sym in results {
+    let (s_id, symb) = (sym.sid, sym.symbol.clone());
     let _news_status = load_news(conn, &s_id, &symb, &mut params, &mut symbol_log);
-    if let Err(_err) = load_intraday(conn, &symb, s_id, SecurityType::Equity) {
-      //todo: improve logging
-      // println!("Error getting intraday prices {} for sid {}", err, sid);
+    // News is published around the clock; prices only change while the market trades.
+    if !schedule.has_new_data(&TradingSession::from_symbol(&sym)) {
+      progress.inc(1);
       continue;
     }
+    if let Err(err) = load_intraday(conn, &symb, s_id, SecurityType::Equity) {
+      progress.println(format!(
+        "Error loading intraday prices for {} (sid {}): {}",
+        symb, s_id, err
+      ));
+    }
     if let Err(err) = load_summary(conn, &symb, s_id) {
       println!("Error loading open close prices {} for sid {}", err, symb);
     }
@@ -115,6 +126,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
   progress.finish_with_message("News loading complete");
   symbol_log.flush()?;
   progress.finish();
+  if let Err(err) = refresh_sentiment_series(conn, None) {
+    println!("Error refreshing the sentiment series {}", err);
+  }
   _ = log_proc_end(conn, pid, 2).unwrap();
   Result::Ok(())
 }
@@ -0,0 +1,101 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![allow(unexpected_cfgs)]
+#[cfg(not(tarpaulin_include))]
+use std::process;
+
+use alpha_vantage_rust::dbfunctions::{
+  base::create_pool,
+  price::{delete_price_duplicates, get_price_duplicates, PriceTable},
+};
+use diesel::{Connection, PgConnection};
+use dotenvy::dotenv;
+
+const USAGE: &str = "usage: dedupe_prices [--dry-run]
+
+  Removes intraday and daily price rows that repeat the time of another row of the same symbol,
+  keeping the most recently inserted one. --dry-run only reports the duplicates per symbol.
+  Run it before the migration that adds the (sid, time) keys to see what it will remove.";
+
+fn parse_args() -> Result<bool, String> {
+  let mut dry_run = false;
+  for arg in std::env::args().skip(1) {
+    match arg.as_str() {
+      "--dry-run" => dry_run = true,
+      other => return Err(format!("Unknown argument: {}", other)),
+    }
+  }
+  Ok(dry_run)
+}
+
+fn dedupe(conn: &mut PgConnection, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+  conn.transaction(|conn| {
+    for table in PriceTable::ALL {
+      let duplicates = get_price_duplicates(conn, table)?;
+      for dup in &duplicates {
+        println!(
+          "{}: sid {} has {} duplicate rows",
+          table.name(),
+          dup.sid,
+          dup.duplicates
+        );
+      }
+      let total: i64 = duplicates.iter().map(|dup| dup.duplicates).sum();
+      match dry_run {
+        true => println!("{}: {} duplicate rows", table.name(), total),
+        false => println!(
+          "{}: deleted {} duplicate rows",
+          table.name(),
+          delete_price_duplicates(conn, table)?
+        ),
+      }
+    }
+    Ok(())
+  })
+}
+
+fn main() {
+  dotenv().ok();
+  let dry_run = parse_args().unwrap_or_else(|err| {
+    eprintln!("{}\n{}", err, USAGE);
+    process::exit(1);
+  });
+  // not establish_connection_or_exit, it runs before the migration that adds the keys
+  let conn = &mut create_pool()
+    .and_then(|pool| pool.get().map_err(Into::into))
+    .unwrap_or_else(|err| {
+      eprintln!("Can't establish db connection: {}", err);
+      process::exit(1);
+    });
+
+  if let Err(err) = dedupe(conn, dry_run) {
+    eprintln!("Cannot dedupe prices: {}", err);
+    process::exit(1);
+  }
+}
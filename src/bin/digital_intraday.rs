@@ -68,10 +68,11 @@ fn main() {
   for (symbol, sid) in results {
     bar.inc(1);
 
-    if let Err(_err) = load_intraday(conn, &symbol, sid, SecurityType::Crypto) {
-      //todo: improve logging
-      // println!("Error getting intraday prices {} for sid {}", err, sid);
-      continue;
+    if let Err(err) = load_intraday(conn, &symbol, sid, SecurityType::Crypto) {
+      bar.println(format!(
+        "Error loading intraday prices for {} (sid {}): {}",
+        symbol, sid, err
+      ));
     }
   }
   bar.finish();
@@ -32,7 +32,8 @@
 use std::process;
 
 use alpha_vantage_rust::{
-  alpha_lib::alpha_io::base::process_digital_symbols, util::processor::file_proc,
+  alpha_lib::alpha_io::base::process_digital_symbols,
+  dbfunctions::base::establish_connection_or_exit, util::processor::file_proc,
 };
 use dotenvy::dotenv;
 
@@ -44,5 +45,6 @@ fn main() {
     eprintln!("Cannot process data files. Check local env setting {}", e);
     process::exit(1);
   });
-  _ = process_digital_symbols(res[0].clone());
+  let conn = &mut establish_connection_or_exit();
+  _ = process_digital_symbols(conn, res[0].clone());
 }
@@ -0,0 +1,250 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![allow(unexpected_cfgs)]
+#[cfg(not(tarpaulin_include))]
+use std::{collections::BTreeMap, fs::File, process};
+
+use alpha_vantage_rust::{
+  alpha_lib::{
+    core::{
+      event_study::{
+        daily_return, intraday_return, relevance_bucket, write_csv, EventReturn, Horizon,
+        ReturnStats,
+      },
+      sentiment_label::SentimentLabel,
+    },
+    misc_functions::get_exe_name,
+  },
+  dbfunctions::{
+    base::establish_connection_or_exit,
+    combined::get_sids_and_names_with_overview,
+    price::{get_intraday_closes, get_summary_closes},
+    process::{get_proc_id_or_insert, log_proc_end, log_proc_start},
+    reports::get_news_events,
+  },
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::PgConnection;
+use dotenvy::dotenv;
+use indicatif::ProgressBar;
+
+const USAGE: &str = "usage: event_study [--symbols A,B,...] [--horizons 5m,1h,1d] \
+                     [--from YYYYMMDD] [--to YYYYMMDD] [--csv PATH]
+
+  Measures the price return over each horizon after every article about a symbol and
+  summarizes it by sentiment label and relevance bucket. The per article returns are
+  written to PATH (default /tmp/event_study.csv).";
+
+/// Calendar days of daily closes loaded around the events, enough to cover weekends and holidays.
+const DAILY_SLACK_DAYS: i64 = 10;
+
+struct Args {
+  symbols: Option<Vec<String>>,
+  horizons: Vec<Horizon>,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+  csv: String,
+}
+
+fn parse_args() -> Result<Args, String> {
+  let mut args = Args {
+    symbols: None,
+    horizons: vec![Horizon::Minutes(5), Horizon::Hours(1), Horizon::Days(1)],
+    from: DateTime::UNIX_EPOCH,
+    to: Utc::now(),
+    csv: "/tmp/event_study.csv".to_string(),
+  };
+  let mut it = std::env::args().skip(1);
+  while let Some(arg) = it.next() {
+    match arg.as_str() {
+      "--symbols" => {
+        args.symbols = Some(
+          next_value(&mut it, &arg)?
+            .split(',')
+            .map(|s| s.trim().to_uppercase())
+            .collect(),
+        )
+      }
+      "--horizons" => {
+        args.horizons = next_value(&mut it, &arg)?
+          .split(',')
+          .map(str::parse::<Horizon>)
+          .collect::<Result<_, _>>()?
+      }
+      "--from" => args.from = parse_date(&next_value(&mut it, &arg)?)?,
+      "--to" => args.to = parse_date(&next_value(&mut it, &arg)?)?,
+      "--csv" => args.csv = next_value(&mut it, &arg)?,
+      _ => return Err(format!("Unknown argument {}", arg)),
+    }
+  }
+  Ok(args)
+}
+
+fn next_value(it: &mut impl Iterator<Item = String>, arg: &str) -> Result<String, String> {
+  it.next()
+    .ok_or_else(|| format!("Missing value for {}", arg))
+}
+
+fn parse_date(inp: &str) -> Result<DateTime<Utc>, String> {
+  NaiveDate::parse_from_str(inp, "%Y%m%d")
+    .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc())
+    .map_err(|e| format!("{}: {}", inp, e))
+}
+
+/// Measures every horizon after every article about a sid.
+fn study_sid(
+  conn: &mut PgConnection,
+  s_id: i64,
+  symbol: &str,
+  args: &Args,
+) -> Result<Vec<EventReturn>, Box<dyn std::error::Error>> {
+  let events = get_news_events(conn, s_id, args.from, args.to)?;
+  let (first, last) = match (events.first(), events.last()) {
+    (Some(first), Some(last)) => (first.published, last.published),
+    _ => return Ok(Vec::new()),
+  };
+
+  let longest = |intraday: bool| {
+    args
+      .horizons
+      .iter()
+      .filter(|h| h.is_intraday() == intraday)
+      .map(|h| h.duration())
+      .max()
+      .unwrap_or_else(Duration::zero)
+  };
+  let ticks = get_intraday_closes(conn, s_id, first - Duration::days(1), last + longest(true))?;
+  let slack = Duration::days(DAILY_SLACK_DAYS);
+  let closes = get_summary_closes(
+    conn,
+    s_id,
+    (first - slack).date_naive(),
+    (last + longest(false) * 2 + slack).date_naive(),
+  )?;
+
+  let mut rows = Vec::new();
+  for event in &events {
+    for horizon in &args.horizons {
+      let forward_return = match horizon {
+        Horizon::Days(days) => daily_return(&closes, event.published, *days),
+        _ => intraday_return(&ticks, event.published, *horizon),
+      };
+      if let Some(forward_return) = forward_return {
+        rows.push(EventReturn {
+          symbol: symbol.to_string(),
+          article: event.article.clone(),
+          published: event.published,
+          label: event.label,
+          relevance: event.relevance,
+          sentiment: event.sentiment,
+          horizon: *horizon,
+          forward_return,
+        });
+      }
+    }
+  }
+  Ok(rows)
+}
+
+fn print_summary(rows: &[EventReturn], horizons: &[Horizon]) {
+  let mut groups: BTreeMap<(usize, SentimentLabel, &str), Vec<f64>> = BTreeMap::new();
+  for row in rows {
+    let order = horizons.iter().position(|h| *h == row.horizon).unwrap_or(0);
+    groups
+      .entry((order, row.label, relevance_bucket(row.relevance)))
+      .or_default()
+      .push(row.forward_return);
+  }
+
+  println!(
+    "{:<8} {:<18} {:<10} {:>7} {:>10} {:>10} {:>10} {:>8}",
+    "horizon", "label", "relevance", "count", "mean", "median", "stddev", "hit"
+  );
+  for ((order, label, bucket), returns) in groups {
+    if let Some(stats) = ReturnStats::from_returns(&returns) {
+      println!(
+        "{:<8} {:<18} {:<10} {:>7} {:>10.5} {:>10.5} {:>10.5} {:>8.3}",
+        horizons[order].to_string(),
+        label.as_str(),
+        bucket,
+        stats.count,
+        stats.mean,
+        stats.median,
+        stats.stddev,
+        stats.hit_rate
+      );
+    }
+  }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+  dotenv().ok();
+  let args = parse_args().unwrap_or_else(|err| {
+    eprintln!("{}\n{}", err, USAGE);
+    process::exit(1);
+  });
+  let conn = &mut establish_connection_or_exit();
+
+  let id_val = get_proc_id_or_insert(conn, &get_exe_name()).unwrap();
+  let pid = log_proc_start(conn, id_val).unwrap();
+
+  let mut symbols = get_sids_and_names_with_overview(conn).unwrap_or_else(|err| {
+    println!("Cannot load results from database {}", err);
+    _ = log_proc_end(conn, pid, 3).unwrap();
+    process::exit(1);
+  });
+  if let Some(wanted) = &args.symbols {
+    symbols.retain(|(_, symbol)| wanted.contains(symbol));
+  }
+
+  let progress = ProgressBar::new(symbols.len() as u64);
+  progress.set_style(
+    indicatif::ProgressStyle::default_bar()
+      .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
+      .expect("Error setting progress bar style")
+      .progress_chars("##-"),
+  );
+
+  let mut rows = Vec::new();
+  for (s_id, symbol) in &symbols {
+    match study_sid(conn, *s_id, symbol, &args) {
+      Ok(mut sid_rows) => rows.append(&mut sid_rows),
+      Err(err) => println!("Error studying {}: {}", symbol, err),
+    }
+    progress.inc(1);
+  }
+  progress.finish_with_message("Event study complete");
+
+  write_csv(File::create(&args.csv)?, &rows)?;
+
+  print_summary(&rows, &args.horizons);
+  println!("{} article returns written to {}", rows.len(), args.csv);
+  _ = log_proc_end(conn, pid, 2).unwrap();
+  Ok(())
+}
@@ -32,10 +32,15 @@
 use std::process;
 
 use alpha_vantage_rust::{
-  alpha_lib::{alpha_io::base::load_intraday, misc_functions::get_exe_name},
+  alpha_lib::{
+    alpha_io::base::{get_market_schedule, load_intraday},
+    core::market_status::TradingSession,
+    misc_functions::get_exe_name,
+  },
+  db_models::Symbol,
   dbfunctions::{
     base::establish_connection_or_exit,
-    combined::get_sids_and_names_with_overview,
+    combined::get_symbols_with_overview,
     process::{get_proc_id_or_insert, log_proc_end, log_proc_start},
   },
   security_types::sec_types::SecurityType,
@@ -49,8 +54,12 @@ fn main() {
   dotenv().ok();
 
   let id_val = get_proc_id_or_insert(conn, &get_exe_name()).unwrap();
+  let schedule = get_market_schedule(conn, id_val).unwrap_or_else(|err| {
+    println!("Cannot determine market schedule {}", err);
+    process::exit(1);
+  });
   let pid = log_proc_start(conn, id_val).unwrap();
-  let results: Vec<(i64, String)> = get_sids_and_names_with_overview(conn).unwrap_or_else(|err| {
+  let results: Vec<Symbol> = get_symbols_with_overview(conn).unwrap_or_else(|err| {
     println!("Cannot load results from database {}", err);
     _ = log_proc_end(conn, pid, 3).unwrap();
     process::exit(1);
@@ -65,14 +74,18 @@ fn main() {
       .progress_chars("##-"),
   );
 
-  for (sid, symbol) in results {
+  for sym in results {
     bar.inc(1);
-
-    if let Err(_err) = load_intraday(conn, &symbol, sid, SecurityType::Equity) {
-      //todo: improve logging
-      // println!("Error getting intraday prices {} for sid {}", err, sid);
+    if !schedule.has_new_data(&TradingSession::from_symbol(&sym)) {
       continue;
     }
+
+    if let Err(err) = load_intraday(conn, &sym.symbol, sym.sid, SecurityType::Equity) {
+      bar.println(format!(
+        "Error loading intraday prices for {} (sid {}): {}",
+        sym.symbol, sym.sid, err
+      ));
+    }
   }
   bar.finish();
   _ = log_proc_end(conn, pid, 2).unwrap();
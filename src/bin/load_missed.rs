@@ -28,8 +28,9 @@
  */
 #![allow(unexpected_cfgs)]
 #[cfg(not(tarpaulin_include))]
-use alpha_vantage_rust::alpha_lib::{
-  alpha_io::base::process_symbols, misc_functions::read_missed_symbols,
+use alpha_vantage_rust::{
+  alpha_lib::{alpha_io::base::process_symbols, misc_functions::read_missed_symbols},
+  dbfunctions::base::establish_connection_or_exit,
 };
 use dotenvy::dotenv;
 
@@ -38,7 +39,8 @@ fn main() {
   if let Ok(secs) = read_missed_symbols("/tmp/symbol_log.txt".to_string()) {
     let mut symbs: Vec<Vec<String>> = Vec::new();
     symbs.push(secs);
-    let res = process_symbols(symbs, true);
+    let conn = &mut establish_connection_or_exit();
+    let res = process_symbols(conn, symbs, true);
     let _ = match res {
       Ok(_) => println!("Operation completed successfully."),
       Err(e) => println!("An error occurred: {}", e),
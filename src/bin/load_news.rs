@@ -36,44 +36,133 @@ use std::{
 
 use alpha_vantage_rust::{
   alpha_lib::{
-    alpha_io::news_loader::{load_news, Params},
+    alpha_io::news_loader::{load_news, load_topic_news, Params},
+    core::{
+      market_calendar::{to_utc, ALPHA_VANTAGE_TZ},
+      news_query::{news_ticker, topic_query_param, SortOrder, MAX_NEWS_LIMIT, NEWS_TOPICS},
+    },
     misc_functions::get_exe_name,
   },
   dbfunctions::{
     author::get_authors,
     base::establish_connection_or_exit,
-    combined::get_sids_and_names_with_overview,
+    combined::{get_sids_and_names_of_type, get_sids_and_names_with_overview},
     process::{get_proc_id_or_insert, log_proc_end, log_proc_start},
+    sentiment_series::refresh_sentiment_series,
     sources::get_sources,
     topic_refs::get_topics,
   },
+  security_types::sec_types::SecurityType,
 };
+use chrono::{DateTime, NaiveDateTime, Utc};
 use dotenvy::dotenv;
 use indicatif::ProgressBar;
 
+const USAGE: &str =
+  "usage: load_news [--incremental] [--topics | --crypto] [--sort LATEST|EARLIEST|RELEVANCE] \
+                     [--limit N] [--from YYYYMMDDTHHMM] [--to YYYYMMDDTHHMM]
+
+  --crypto  load the news of the digital currencies instead of the equities";
+
+#[derive(Default)]
+struct Args {
+  incremental: bool,
+  by_topic: bool,
+  crypto: bool,
+  sort: Option<SortOrder>,
+  limit: Option<u32>,
+  time_from: Option<DateTime<Utc>>,
+  time_to: Option<DateTime<Utc>>,
+}
+
+fn parse_args() -> Result<Args, String> {
+  let mut args = Args {
+    limit: Some(MAX_NEWS_LIMIT),
+    ..Default::default()
+  };
+  let mut it = std::env::args().skip(1);
+  while let Some(arg) = it.next() {
+    match arg.as_str() {
+      "--incremental" => args.incremental = true,
+      "--topics" => args.by_topic = true,
+      "--crypto" => args.crypto = true,
+      "--sort" => args.sort = Some(next_value(&mut it, &arg)?.parse::<SortOrder>()?),
+      "--limit" => {
+        let limit = next_value(&mut it, &arg)?;
+        args.limit = Some(
+          limit
+            .parse::<u32>()
+            .map_err(|e| format!("{}: {}", limit, e))?,
+        );
+      }
+      "--from" => args.time_from = Some(parse_time(&next_value(&mut it, &arg)?)?),
+      "--to" => args.time_to = Some(parse_time(&next_value(&mut it, &arg)?)?),
+      _ => return Err(format!("Unknown argument {}", arg)),
+    }
+  }
+  if args.by_topic && (args.incremental || args.crypto) {
+    return Err("--topics cannot be combined with --incremental or --crypto".to_string());
+  }
+  Ok(args)
+}
+
+fn next_value(it: &mut impl Iterator<Item = String>, arg: &str) -> Result<String, String> {
+  it.next()
+    .ok_or_else(|| format!("Missing value for {}", arg))
+}
+
+/// Parses a time in the YYYYMMDDTHHMM format of the NEWS_SENTIMENT endpoint (US/Eastern).
+fn parse_time(inp: &str) -> Result<DateTime<Utc>, String> {
+  let local =
+    NaiveDateTime::parse_from_str(inp, "%Y%m%dT%H%M").map_err(|e| format!("{}: {}", inp, e))?;
+  to_utc(local, ALPHA_VANTAGE_TZ).ok_or_else(|| format!("{} is not a valid US/Eastern time", inp))
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
   dotenv().ok();
+  let args = parse_args().unwrap_or_else(|err| {
+    eprintln!("{}\n{}", err, USAGE);
+    process::exit(1);
+  });
   let conn = &mut establish_connection_or_exit();
 
   let id_val = get_proc_id_or_insert(conn, &get_exe_name()).unwrap();
 
   let pid = log_proc_start(conn, id_val).unwrap();
-  let results: Vec<(i64, String)> = get_sids_and_names_with_overview(conn).unwrap_or_else(|err| {
+  let equities: Vec<(i64, String)> = get_sids_and_names_with_overview(conn).unwrap_or_else(|err| {
     println!("Cannot load results from database {}", err);
     _ = log_proc_end(conn, pid, 3).unwrap();
     process::exit(1);
   });
+  let digital: Vec<(i64, String)> = get_sids_and_names_of_type(conn, "Crypto")
+    .unwrap_or_else(|err| {
+      println!("Cannot load results from database {}", err);
+      _ = log_proc_end(conn, pid, 3).unwrap();
+      process::exit(1);
+    })
+    .into_iter()
+    .map(|(sid, symbol)| (sid, news_ticker(SecurityType::Crypto, &symbol)))
+    .collect();
 
-  let count_of_sids = results.len();
-
-  let mut params = Params::default();
+  let mut params = Params {
+    incremental: args.incremental,
+    ..Default::default()
+  };
+  params.query.sort = args.sort;
+  params.query.limit = args.limit;
+  params.query.time_from = args.time_from;
+  params.query.time_to = args.time_to;
   let topics = get_topics(conn)?;
   let authors = get_authors(conn)?;
   let sources = get_sources(conn)?;
 
-  for (sid, name) in results.iter() {
+  for (sid, name) in equities.iter().chain(digital.iter()) {
     params.names_to_sid.insert(name.clone(), *sid);
   }
+  let results = match args.crypto {
+    true => digital,
+    false => equities,
+  };
 
   params.topics = topics.iter().map(|t| (t.name.clone(), t.id)).collect();
   params.authors = authors
@@ -86,7 +175,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     .collect();
   let mut symbol_log: BufWriter<File> = BufWriter::new(File::create("/tmp/symbol_log.txt")?);
 
-  let progress = ProgressBar::new(count_of_sids as u64);
+  // Topic mode queries every topic known in topicrefs, or all topics on a fresh database.
+  let mut topic_params: Vec<&str> = params
+    .topics
+    .keys()
+    .filter_map(|name| topic_query_param(name))
+    .collect();
+  if topic_params.is_empty() {
+    topic_params = NEWS_TOPICS.iter().map(|(param, _)| *param).collect();
+  }
+  topic_params.sort_unstable();
+  topic_params.dedup();
+
+  let count = match args.by_topic {
+    true => topic_params.len(),
+    false => results.len(),
+  };
+  let progress = ProgressBar::new(count as u64);
   progress.set_style(
     indicatif::ProgressStyle::default_bar()
       .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
@@ -94,13 +199,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
       .progress_chars("##-"),
   );
 
-  for (s_id, symb) in results {
-    let _news_status = load_news(conn, &s_id, &symb, &mut params, &mut symbol_log);
-    progress.inc(1);
+  if args.by_topic {
+    for topic in topic_params {
+      let _news_status = load_topic_news(conn, topic, &mut params, &mut symbol_log);
+      progress.inc(1);
+    }
+  } else {
+    for (s_id, tkr) in results.iter() {
+      let _news_status = load_news(conn, s_id, tkr, &mut params, &mut symbol_log);
+      progress.inc(1);
+    }
   }
   progress.finish_with_message("News loading complete");
   symbol_log.flush()?;
   progress.finish();
+  if let Err(err) = refresh_sentiment_series(conn, None) {
+    println!("Error refreshing the sentiment series {}", err);
+  }
   _ = log_proc_end(conn, pid, 2).unwrap();
   Result::Ok(())
 }
@@ -2,7 +2,10 @@
 #[cfg(not(tarpaulin_include))]
 use std::process;
 
-use alpha_vantage_rust::{alpha_lib::alpha_io::base::process_symbols, util::processor::file_proc};
+use alpha_vantage_rust::{
+  alpha_lib::alpha_io::base::process_symbols, dbfunctions::base::establish_connection_or_exit,
+  util::processor::file_proc,
+};
 use dotenvy::dotenv;
 
 ///
@@ -22,7 +25,8 @@ fn main() {
     }
   };
 
-  let res = process_symbols(sec_vec, false);
+  let conn = &mut establish_connection_or_exit();
+  let res = process_symbols(conn, sec_vec, false);
   match res {
     Ok(_) => println!("Operation completed successfully."),
     Err(e) => println!("An error occurred: {}", e),
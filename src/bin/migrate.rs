@@ -0,0 +1,115 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![allow(unexpected_cfgs)]
+#[cfg(not(tarpaulin_include))]
+use std::process;
+
+use alpha_vantage_rust::dbfunctions::{
+  base::create_pool,
+  migrations::{get_migration_status, revert_last_migration, run_pending_migrations},
+};
+use diesel::PgConnection;
+use dotenvy::dotenv;
+
+const USAGE: &str = "usage: migrate <up | down [count] | status>
+
+  Applies the migrations embedded in this build to the database in DATABASE_URL.
+  up      runs every pending migration
+  down    reverts the last applied migration, or the last <count>
+  status  lists the migrations and whether each has been applied";
+
+enum Command {
+  Up,
+  Down(usize),
+  Status,
+}
+
+fn parse_args() -> Result<Command, String> {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  match args
+    .iter()
+    .map(String::as_str)
+    .collect::<Vec<_>>()
+    .as_slice()
+  {
+    ["up"] => Ok(Command::Up),
+    ["down"] => Ok(Command::Down(1)),
+    ["down", count] => count
+      .parse()
+      .map(Command::Down)
+      .map_err(|_| format!("Invalid count: {}", count)),
+    ["status"] => Ok(Command::Status),
+    [] => Err("Missing command".to_string()),
+    other => Err(format!("Unknown arguments: {}", other.join(" "))),
+  }
+}
+
+fn migrate(conn: &mut PgConnection, command: Command) -> Result<(), Box<dyn std::error::Error>> {
+  match command {
+    Command::Up => {
+      let versions = run_pending_migrations(conn)?;
+      for version in &versions {
+        println!("Applied {}", version);
+      }
+      println!("{} migrations applied", versions.len());
+    }
+    Command::Down(count) => {
+      for _ in 0..count {
+        println!("Reverted {}", revert_last_migration(conn)?);
+      }
+    }
+    Command::Status => {
+      for status in get_migration_status(conn)? {
+        let mark = if status.applied { "x" } else { " " };
+        println!("[{}] {}", mark, status.name);
+      }
+    }
+  }
+  Ok(())
+}
+
+fn main() {
+  dotenv().ok();
+  let command = parse_args().unwrap_or_else(|err| {
+    eprintln!("{}\n{}", err, USAGE);
+    process::exit(1);
+  });
+  // not establish_connection_or_exit, which refuses a database that is not migrated
+  let conn = &mut create_pool()
+    .and_then(|pool| pool.get().map_err(Into::into))
+    .unwrap_or_else(|err| {
+      eprintln!("Can't establish db connection: {}", err);
+      process::exit(1);
+    });
+
+  if let Err(err) = migrate(conn, command) {
+    eprintln!("Cannot migrate: {}", err);
+    process::exit(1);
+  }
+}
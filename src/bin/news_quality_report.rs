@@ -0,0 +1,141 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![allow(unexpected_cfgs)]
+#[cfg(not(tarpaulin_include))]
+use std::{
+  fs::File,
+  io::{self, BufWriter, Write},
+  process,
+};
+
+use alpha_vantage_rust::dbfunctions::{
+  base::establish_connection_or_exit,
+  news_quality::{get_quality_stats, get_quality_volume, write_csv, Grouping},
+  topic_trends::Period,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use dotenvy::dotenv;
+
+const USAGE: &str = "usage: news_quality_report [--by source|author] [--volume] \
+                     [--period day|week|month] [--format json|csv] [--from YYYYMMDD] \
+                     [--to YYYYMMDD] [--out PATH]
+
+  Prints the article volume, sentiment, relevance and next-day price agreement of every
+  source (default) or author. --volume prints the article count per period instead.";
+
+struct Args {
+  grouping: Grouping,
+  volume: bool,
+  period: Period,
+  csv: bool,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+  out: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+  let mut args = Args {
+    grouping: Grouping::Source,
+    volume: false,
+    period: Period::Week,
+    csv: false,
+    from: DateTime::UNIX_EPOCH,
+    to: Utc::now(),
+    out: None,
+  };
+  let mut it = std::env::args().skip(1);
+  while let Some(arg) = it.next() {
+    match arg.as_str() {
+      "--by" => {
+        args.grouping = next_value(&mut it, &arg)?
+          .parse::<Grouping>()
+          .map_err(|e| e.to_string())?
+      }
+      "--volume" => args.volume = true,
+      "--period" => {
+        args.period = next_value(&mut it, &arg)?
+          .parse::<Period>()
+          .map_err(|e| e.to_string())?
+      }
+      "--format" => {
+        args.csv = match next_value(&mut it, &arg)?.as_str() {
+          "csv" => true,
+          "json" => false,
+          other => return Err(format!("Unknown format {}", other)),
+        }
+      }
+      "--from" => args.from = parse_date(&next_value(&mut it, &arg)?)?,
+      "--to" => args.to = parse_date(&next_value(&mut it, &arg)?)?,
+      "--out" => args.out = Some(next_value(&mut it, &arg)?),
+      _ => return Err(format!("Unknown argument {}", arg)),
+    }
+  }
+  Ok(args)
+}
+
+fn next_value(it: &mut impl Iterator<Item = String>, arg: &str) -> Result<String, String> {
+  it.next()
+    .ok_or_else(|| format!("Missing value for {}", arg))
+}
+
+fn parse_date(inp: &str) -> Result<DateTime<Utc>, String> {
+  NaiveDate::parse_from_str(inp, "%Y%m%d")
+    .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc())
+    .map_err(|e| format!("{}: {}", inp, e))
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+  dotenv().ok();
+  let args = parse_args().unwrap_or_else(|err| {
+    eprintln!("{}\n{}", err, USAGE);
+    process::exit(1);
+  });
+  let conn = &mut establish_connection_or_exit();
+
+  let mut out: Box<dyn Write> = match &args.out {
+    Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+    None => Box::new(io::stdout().lock()),
+  };
+
+  if args.volume {
+    let volume = get_quality_volume(conn, args.grouping, args.period, args.from, args.to)?;
+    match args.csv {
+      true => write_csv(&mut out, &volume)?,
+      false => writeln!(out, "{}", serde_json::to_string_pretty(&volume)?)?,
+    }
+  } else {
+    let stats = get_quality_stats(conn, args.grouping, args.from, args.to)?;
+    match args.csv {
+      true => write_csv(&mut out, &stats)?,
+      false => writeln!(out, "{}", serde_json::to_string_pretty(&stats)?)?,
+    }
+  }
+  out.flush()?;
+  Ok(())
+}
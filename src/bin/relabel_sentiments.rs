@@ -0,0 +1,104 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![allow(unexpected_cfgs)]
+#[cfg(not(tarpaulin_include))]
+use std::process;
+
+use alpha_vantage_rust::{
+  alpha_lib::{core::sentiment_label::SentimentThresholds, misc_functions::get_exe_name},
+  dbfunctions::{
+    base::establish_connection_or_exit,
+    process::{get_proc_id_or_insert, log_proc_end, log_proc_start},
+    sentiment_labels::relabel_sentiments,
+    sentiment_series::refresh_sentiment_series,
+  },
+};
+use chrono::DateTime;
+use dotenvy::dotenv;
+
+const USAGE: &str = "usage: relabel_sentiments BEARISH SOMEWHAT_BEARISH SOMEWHAT_BULLISH BULLISH
+
+  Re-derives the stored overall and ticker sentiment labels from their scores. A score at or
+  below BEARISH is Bearish, at or below SOMEWHAT_BEARISH Somewhat-Bearish, below SOMEWHAT_BULLISH
+  Neutral, below BULLISH Somewhat-Bullish and Bullish otherwise.
+  Alpha Vantage uses -0.35 -0.15 0.15 0.35. The thresholds are recorded as a sentiment definition
+  and the sentiment series are rebuilt.";
+
+fn parse_args() -> Result<SentimentThresholds, String> {
+  let bounds = std::env::args()
+    .skip(1)
+    .map(|arg| arg.parse::<f64>().map_err(|e| format!("{}: {}", arg, e)))
+    .collect::<Result<Vec<f64>, String>>()?;
+  let thresholds = match bounds[..] {
+    [bearish, somewhat_bearish, somewhat_bullish, bullish] => SentimentThresholds {
+      bearish,
+      somewhat_bearish,
+      somewhat_bullish,
+      bullish,
+    },
+    _ => return Err("Expected four thresholds".to_string()),
+  };
+  match bounds.windows(2).all(|w| w[0] < w[1]) {
+    true => Ok(thresholds),
+    false => Err("Thresholds must be increasing".to_string()),
+  }
+}
+
+fn main() {
+  dotenv().ok();
+  let thresholds = parse_args().unwrap_or_else(|err| {
+    eprintln!("{}\n{}", err, USAGE);
+    process::exit(1);
+  });
+  let conn = &mut establish_connection_or_exit();
+
+  let id_val = get_proc_id_or_insert(conn, &get_exe_name()).unwrap();
+  let pid = log_proc_start(conn, id_val).unwrap();
+
+  match relabel_sentiments(conn, &thresholds) {
+    Ok((def_id, feeds, tickers)) => println!(
+      "Relabelled {} overall and {} ticker sentiments with definition {}",
+      feeds, tickers, def_id
+    ),
+    Err(err) => {
+      eprintln!("Cannot relabel sentiments: {}", err);
+      _ = log_proc_end(conn, pid, 3).unwrap();
+      process::exit(1);
+    }
+  }
+  match refresh_sentiment_series(conn, Some(DateTime::UNIX_EPOCH)) {
+    Ok(buckets) => println!("{} sentiment buckets rebuilt", buckets),
+    Err(err) => {
+      eprintln!("Cannot refresh the sentiment series: {}", err);
+      _ = log_proc_end(conn, pid, 3).unwrap();
+      process::exit(1);
+    }
+  }
+  _ = log_proc_end(conn, pid, 2).unwrap();
+}
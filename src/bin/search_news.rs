@@ -0,0 +1,123 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![allow(unexpected_cfgs)]
+#[cfg(not(tarpaulin_include))]
+use std::process;
+
+use alpha_vantage_rust::{
+  alpha_lib::core::{
+    market_calendar::{to_utc, ALPHA_VANTAGE_TZ},
+    sentiment_label::SentimentLabel,
+  },
+  dbfunctions::{
+    base::establish_connection_or_exit,
+    news_search::{search_news, NewsSearch},
+  },
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use dotenvy::dotenv;
+
+const USAGE: &str = "usage: search_news [TEXT] [--symbol SYMBOL] [--source NAME] [--topic NAME] \
+                     [--sentiment LABEL] [--from YYYYMMDDTHHMM] [--to YYYYMMDDTHHMM] [--page N] \
+                     [--page-size N] [--json]
+
+  TEXT  words to search for in titles and summaries, e.g. '\"rate cut\" -bank'";
+
+fn parse_args() -> Result<(NewsSearch, bool), String> {
+  let mut search = NewsSearch::default();
+  let mut json = false;
+  let mut it = std::env::args().skip(1);
+  while let Some(arg) = it.next() {
+    match arg.as_str() {
+      "--symbol" => search.symbol = Some(next_value(&mut it, &arg)?),
+      "--source" => search.source = Some(next_value(&mut it, &arg)?),
+      "--topic" => search.topic = Some(next_value(&mut it, &arg)?),
+      "--sentiment" => {
+        search.sentiment_label = Some(next_value(&mut it, &arg)?.parse::<SentimentLabel>()?)
+      }
+      "--from" => search.from = Some(parse_time(&next_value(&mut it, &arg)?)?),
+      "--to" => search.to = Some(parse_time(&next_value(&mut it, &arg)?)?),
+      "--page" => search.page = parse_number(&next_value(&mut it, &arg)?)?,
+      "--page-size" => search.page_size = parse_number(&next_value(&mut it, &arg)?)?,
+      "--json" => json = true,
+      _ if arg.starts_with("--") || search.text.is_some() => {
+        return Err(format!("Unknown argument {}", arg))
+      }
+      _ => search.text = Some(arg),
+    }
+  }
+  Ok((search, json))
+}
+
+fn next_value(it: &mut impl Iterator<Item = String>, arg: &str) -> Result<String, String> {
+  it.next()
+    .ok_or_else(|| format!("Missing value for {}", arg))
+}
+
+fn parse_number(inp: &str) -> Result<i64, String> {
+  inp.parse::<i64>().map_err(|e| format!("{}: {}", inp, e))
+}
+
+/// Parses a time in the YYYYMMDDTHHMM format used by load_news (US/Eastern).
+fn parse_time(inp: &str) -> Result<DateTime<Utc>, String> {
+  let local =
+    NaiveDateTime::parse_from_str(inp, "%Y%m%dT%H%M").map_err(|e| format!("{}: {}", inp, e))?;
+  to_utc(local, ALPHA_VANTAGE_TZ).ok_or_else(|| format!("{} is not a valid US/Eastern time", inp))
+}
+
+fn main() {
+  dotenv().ok();
+  let (search, json) = parse_args().unwrap_or_else(|err| {
+    eprintln!("{}\n{}", err, USAGE);
+    process::exit(1);
+  });
+  let conn = &mut establish_connection_or_exit();
+
+  let hits = search_news(conn, &search).unwrap_or_else(|err| {
+    eprintln!("Search failed: {}", err);
+    process::exit(1);
+  });
+
+  if json {
+    println!("{}", serde_json::to_string_pretty(&hits).unwrap());
+    return;
+  }
+  println!("{} results (page {})", hits.len(), search.page);
+  for hit in hits {
+    println!(
+      "{} [{}] {:.3} {}\n  {}\n  {}\n",
+      hit.published.format("%Y-%m-%d %H:%M"),
+      hit.source_name,
+      hit.rank,
+      hit.title,
+      hit.url,
+      hit.snippet
+    );
+  }
+}
@@ -29,29 +29,25 @@
 
 #![allow(unexpected_cfgs)]
 #[cfg(not(tarpaulin_include))]
-use alpha_vantage_rust::m_get_news_stories;
-extern crate diesel;
-extern crate serde;
-use alpha_vantage_rust::dbfunctions::base::establish_connection_or_exit;
-use diesel::{prelude::*, sql_query};
-use serde_derive::Serialize;
-
-#[derive(QueryableByName, Debug, Serialize)]
-pub struct NewsStories {
-  #[diesel(sql_type = diesel::sql_types::Varchar)]
-  pub title: String,
-  #[diesel(sql_type = diesel::sql_types::Varchar)]
-  pub url: String,
-}
+use alpha_vantage_rust::dbfunctions::{
+  base::establish_connection_or_exit,
+  reports::{get_news_count, get_news_stories, get_sentiment_stats},
+};
 
 fn main() {
-  let symbol = "MC";
-  let query = m_get_news_stories!(symbol);
+  let symbol = std::env::args().nth(1).unwrap_or_else(|| "MC".to_string());
   let connection = &mut establish_connection_or_exit();
-  let news_stories: Vec<NewsStories> = sql_query(query)
-    .get_results(connection)
-    .expect("Error loading news stories");
+  let news_stories = get_news_stories(connection, &symbol).expect("Error loading news stories");
+
+  let story_count = get_news_count(connection, &symbol).expect("Error counting news stories");
+  let sentiment = get_sentiment_stats(connection, &symbol).expect("Error loading sentiment");
 
-  println!("{} News Stories for {}", news_stories.len(), symbol);
+  println!(
+    "{} News Stories for {} ({} distinct)",
+    news_stories.len(),
+    symbol,
+    story_count
+  );
   println!("{:#?}", news_stories);
+  println!("{:#?}", sentiment);
 }
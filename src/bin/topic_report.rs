@@ -0,0 +1,159 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![allow(unexpected_cfgs)]
+#[cfg(not(tarpaulin_include))]
+use std::process;
+
+use alpha_vantage_rust::dbfunctions::{
+  base::establish_connection_or_exit,
+  topic_refs::get_topics,
+  topic_trends::{
+    get_top_symbols_for_topic, get_topic_share_changes, get_topic_volume, week_start, Period,
+  },
+};
+use chrono::{Duration, Utc};
+use dotenvy::dotenv;
+
+const USAGE: &str = "usage: topic_report [--period day|week|month] [--weeks N] [--topic NAME] \
+                     [--limit N]
+
+  Prints the article volume and mean sentiment of each topic per period over the last N weeks
+  (default 8), the symbols with the most articles per topic (or only for NAME) and the topics
+  that gained share in the last complete week.";
+
+struct Args {
+  period: Period,
+  weeks: i64,
+  topic: Option<String>,
+  limit: i64,
+}
+
+fn parse_args() -> Result<Args, String> {
+  let mut args = Args {
+    period: Period::Week,
+    weeks: 8,
+    topic: None,
+    limit: 5,
+  };
+  let mut it = std::env::args().skip(1);
+  while let Some(arg) = it.next() {
+    match arg.as_str() {
+      "--period" => {
+        args.period = next_value(&mut it, &arg)?
+          .parse::<Period>()
+          .map_err(|e| e.to_string())?
+      }
+      "--weeks" => args.weeks = parse_count(&next_value(&mut it, &arg)?)?,
+      "--topic" => args.topic = Some(next_value(&mut it, &arg)?),
+      "--limit" => args.limit = parse_count(&next_value(&mut it, &arg)?)?,
+      _ => return Err(format!("Unknown argument {}", arg)),
+    }
+  }
+  Ok(args)
+}
+
+fn next_value(it: &mut impl Iterator<Item = String>, arg: &str) -> Result<String, String> {
+  it.next()
+    .ok_or_else(|| format!("Missing value for {}", arg))
+}
+
+fn parse_count(inp: &str) -> Result<i64, String> {
+  match inp.parse::<i64>() {
+    Ok(n) if n > 0 => Ok(n),
+    _ => Err(format!("{} is not a positive number", inp)),
+  }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+  dotenv().ok();
+  let args = parse_args().unwrap_or_else(|err| {
+    eprintln!("{}\n{}", err, USAGE);
+    process::exit(1);
+  });
+  let conn = &mut establish_connection_or_exit();
+
+  let now = Utc::now();
+  let from = week_start(now - Duration::weeks(args.weeks));
+
+  println!("Topic volume per {}", args.period);
+  println!(
+    "{:<28} {:<12} {:>8} {:>10} {:>10}",
+    "topic", "bucket", "articles", "sentiment", "relevance"
+  );
+  for volume in get_topic_volume(conn, args.period, from, now)? {
+    println!(
+      "{:<28} {:<12} {:>8} {:>10.4} {:>10.4}",
+      volume.topic,
+      volume.bucket.format("%Y-%m-%d").to_string(),
+      volume.articles,
+      volume.mean_sentiment.unwrap_or_default(),
+      volume.mean_relevance.unwrap_or_default()
+    );
+  }
+
+  let topics = match &args.topic {
+    Some(topic) => vec![topic.clone()],
+    None => get_topics(conn)?.into_iter().map(|t| t.name).collect(),
+  };
+  println!("\nTop symbols per topic");
+  for topic in topics {
+    let symbols = get_top_symbols_for_topic(conn, &topic, from, now, args.limit)?;
+    if symbols.is_empty() {
+      continue;
+    }
+    println!("{}", topic);
+    for symbol in symbols {
+      println!(
+        "  {:<10} {:>8} {:>10.4} {:>10.4}",
+        symbol.symbol,
+        symbol.articles,
+        symbol.mean_relevance.unwrap_or_default(),
+        symbol.mean_sentiment.unwrap_or_default()
+      );
+    }
+  }
+
+  let last_week = week_start(week_start(now) - Duration::days(1));
+  println!(
+    "\nShare change for the week of {}",
+    last_week.format("%Y-%m-%d")
+  );
+  for share in get_topic_share_changes(conn, last_week)? {
+    println!(
+      "{:<28} {:>6} {:>7.2}% {:>6} {:>7.2}% {:>+8.2}",
+      share.topic,
+      share.articles,
+      share.share * 100.0,
+      share.previous_articles,
+      share.previous_share * 100.0,
+      share.change()
+    );
+  }
+  Ok(())
+}
@@ -27,13 +27,17 @@
  * SOFTWARE.
  */
 
+use bigdecimal::BigDecimal;
 use chrono::{prelude::*, NaiveDateTime};
 use diesel::prelude::*;
 
-use crate::schema::{
-  articles, authormaps, authors, feeds, intradayprices, newsoverviews, overviewexts, overviews,
-  procstates, proctypes, sources, summaryprices, symbols, tickersentiments, topicmaps, topicrefs,
-  topstats,
+use crate::{
+  alpha_lib::core::sentiment_label::SentimentLabel,
+  schema::{
+    articles, authormaps, authors, feeds, intradayprices, newsoverviews, overview_history,
+    overviewexts, overviews, priceloads, procstates, proctypes, sentimentdefinitions, sources,
+    summaryprices, symbols, tickersentiments, topicmaps, topicrefs, topstats,
+  },
 };
 
 #[derive(Queryable, Debug)]
@@ -78,53 +82,54 @@ pub struct NewSymbol<'a> {
 pub struct Overview {
   pub sid: i64,
   pub symbol: String,
-  pub name: String,
-  pub description: String,
-  pub cik: String,
-  pub exch: String,
-  pub curr: String,
-  pub country: String,
-  pub sector: String,
-  pub industry: String,
-  pub address: String,
-  pub fiscalyearend: String,
-  pub latestquarter: NaiveDate,
-  pub marketcapitalization: i64,
-  pub ebitda: i64,
-  pub peratio: f32,
-  pub pegratio: f32,
-  pub bookvalue: f64,
-  pub dividendpershare: f32,
-  pub dividendyield: f32,
-  pub eps: f32,
+  pub name: Option<String>,
+  pub description: Option<String>,
+  pub cik: Option<String>,
+  pub exch: Option<String>,
+  pub curr: Option<String>,
+  pub country: Option<String>,
+  pub sector: Option<String>,
+  pub industry: Option<String>,
+  pub address: Option<String>,
+  pub fiscalyearend: Option<String>,
+  pub latestquarter: Option<NaiveDate>,
+  pub marketcapitalization: Option<i64>,
+  pub ebitda: Option<i64>,
+  pub peratio: Option<BigDecimal>,
+  pub pegratio: Option<BigDecimal>,
+  pub bookvalue: Option<BigDecimal>,
+  pub dividendpershare: Option<BigDecimal>,
+  pub dividendyield: Option<BigDecimal>,
+  pub eps: Option<BigDecimal>,
   pub c_time: NaiveDateTime,
   pub mod_time: NaiveDateTime,
 }
 
-#[derive(Insertable, Debug)]
-#[diesel(table_name = overviews)]
+#[derive(Insertable, AsChangeset, Debug)]
+#[diesel(table_name = overviews, treat_none_as_null = true)]
 pub struct NewOverview<'a> {
   pub sid: &'a i64,
   pub symbol: &'a str,
-  pub name: &'a str,
-  pub description: &'a str,
-  pub cik: &'a str,
-  pub exch: &'a str,
-  pub curr: &'a str,
-  pub country: &'a str,
-  pub sector: &'a str,
-  pub industry: &'a str,
-  pub address: &'a str,
-  pub fiscalyearend: &'a str,
-  pub latestquarter: &'a NaiveDate,
-  pub marketcapitalization: &'a i64,
-  pub ebitda: &'a i64,
-  pub peratio: &'a f32,
-  pub pegratio: &'a f32,
-  pub bookvalue: &'a f64,
-  pub dividendpershare: &'a f32,
-  pub dividendyield: &'a f32,
-  pub eps: &'a f32,
+  pub name: Option<&'a str>,
+  pub description: Option<&'a str>,
+  pub cik: Option<&'a str>,
+  pub exch: Option<&'a str>,
+  pub curr: Option<&'a str>,
+  pub country: Option<&'a str>,
+  pub sector: Option<&'a str>,
+  pub industry: Option<&'a str>,
+  pub address: Option<&'a str>,
+  pub fiscalyearend: Option<&'a str>,
+  pub latestquarter: Option<&'a NaiveDate>,
+  pub marketcapitalization: Option<&'a i64>,
+  pub ebitda: Option<&'a i64>,
+  pub peratio: Option<&'a BigDecimal>,
+  pub pegratio: Option<&'a BigDecimal>,
+  pub bookvalue: Option<&'a BigDecimal>,
+  pub dividendpershare: Option<&'a BigDecimal>,
+  pub dividendyield: Option<&'a BigDecimal>,
+  pub eps: Option<&'a BigDecimal>,
+  #[diesel(skip_update)]
   pub c_time: &'a NaiveDateTime,
   pub mod_time: &'a NaiveDateTime,
 }
@@ -132,65 +137,82 @@ pub struct NewOverview<'a> {
 #[derive(Queryable, Debug)]
 pub struct Overviewext {
   pub sid: i64,
-  pub revenuepersharettm: f32,
-  pub profitmargin: f32,
-  pub operatingmarginttm: f32,
-  pub returnonassetsttm: f32,
-  pub returnonequityttm: f32,
-  pub revenuettm: i64,
-  pub grossprofitttm: i64,
-  pub dilutedepsttm: f32,
-  pub quarterlyearningsgrowthyoy: f32,
-  pub quarterlyrevenuegrowthyoy: f32,
-  pub analysttargetprice: f32,
-  pub trailingpe: f32,
-  pub forwardpe: f32,
-  pub pricetosalesratiottm: f32,
-  pub pricetobookratio: f32,
-  pub evtorevenue: f32,
-  pub evtoebitda: f32,
-  pub beta: f64,
-  pub annweekhigh: f64,
-  pub annweeklow: f64,
-  pub fiftydaymovingaverage: f64,
-  pub twohdaymovingaverage: f64,
-  pub sharesoutstanding: f64,
-  pub dividenddate: NaiveDate,
-  pub exdividenddate: NaiveDate,
+  pub revenuepersharettm: Option<BigDecimal>,
+  pub profitmargin: Option<BigDecimal>,
+  pub operatingmarginttm: Option<BigDecimal>,
+  pub returnonassetsttm: Option<BigDecimal>,
+  pub returnonequityttm: Option<BigDecimal>,
+  pub revenuettm: Option<i64>,
+  pub grossprofitttm: Option<i64>,
+  pub dilutedepsttm: Option<BigDecimal>,
+  pub quarterlyearningsgrowthyoy: Option<BigDecimal>,
+  pub quarterlyrevenuegrowthyoy: Option<BigDecimal>,
+  pub analysttargetprice: Option<BigDecimal>,
+  pub trailingpe: Option<BigDecimal>,
+  pub forwardpe: Option<BigDecimal>,
+  pub pricetosalesratiottm: Option<BigDecimal>,
+  pub pricetobookratio: Option<BigDecimal>,
+  pub evtorevenue: Option<BigDecimal>,
+  pub evtoebitda: Option<BigDecimal>,
+  pub beta: Option<f64>,
+  pub annweekhigh: Option<BigDecimal>,
+  pub annweeklow: Option<BigDecimal>,
+  pub fiftydaymovingaverage: Option<BigDecimal>,
+  pub twohdaymovingaverage: Option<BigDecimal>,
+  pub sharesoutstanding: Option<i64>,
+  pub dividenddate: Option<NaiveDate>,
+  pub exdividenddate: Option<NaiveDate>,
   pub c_time: NaiveDateTime,
   pub mod_time: NaiveDateTime,
 }
 
-/// Overviewexts table exists to minimize compile time Diesel 64 column feature is too slow.
+#[derive(Queryable, Debug)]
+pub struct OverviewHistory {
+  pub id: i32,
+  pub sid: i64,
+  pub fetched_at: DateTime<Utc>,
+  pub overview: serde_json::Value,
+}
+
 #[derive(Insertable, Debug)]
-#[diesel(table_name = overviewexts)]
+#[diesel(table_name = overview_history)]
+pub struct NewOverviewHistory<'a> {
+  pub sid: &'a i64,
+  pub fetched_at: &'a DateTime<Utc>,
+  pub overview: &'a serde_json::Value,
+}
+
+/// Overviewexts table exists to minimize compile time Diesel 64 column feature is too slow.
+#[derive(Insertable, AsChangeset, Debug)]
+#[diesel(table_name = overviewexts, treat_none_as_null = true)]
 pub struct NewOverviewext<'a> {
   pub sid: &'a i64,
-  pub revenuepersharettm: &'a f32,
-  pub profitmargin: &'a f32,
-  pub operatingmarginttm: &'a f32,
-  pub returnonassetsttm: &'a f32,
-  pub returnonequityttm: &'a f32,
-  pub revenuettm: &'a i64,
-  pub grossprofitttm: &'a i64,
-  pub dilutedepsttm: &'a f32,
-  pub quarterlyearningsgrowthyoy: &'a f32,
-  pub quarterlyrevenuegrowthyoy: &'a f32,
-  pub analysttargetprice: &'a f32,
-  pub trailingpe: &'a f32,
-  pub forwardpe: &'a f32,
-  pub pricetosalesratiottm: &'a f32,
-  pub pricetobookratio: &'a f32,
-  pub evtorevenue: &'a f32,
-  pub evtoebitda: &'a f32,
-  pub beta: &'a f64,
-  pub annweekhigh: &'a f64,
-  pub annweeklow: &'a f64,
-  pub fiftydaymovingaverage: &'a f64,
-  pub twohdaymovingaverage: &'a f64,
-  pub sharesoutstanding: &'a f64,
-  pub dividenddate: &'a NaiveDate,
-  pub exdividenddate: &'a NaiveDate,
+  pub revenuepersharettm: Option<&'a BigDecimal>,
+  pub profitmargin: Option<&'a BigDecimal>,
+  pub operatingmarginttm: Option<&'a BigDecimal>,
+  pub returnonassetsttm: Option<&'a BigDecimal>,
+  pub returnonequityttm: Option<&'a BigDecimal>,
+  pub revenuettm: Option<&'a i64>,
+  pub grossprofitttm: Option<&'a i64>,
+  pub dilutedepsttm: Option<&'a BigDecimal>,
+  pub quarterlyearningsgrowthyoy: Option<&'a BigDecimal>,
+  pub quarterlyrevenuegrowthyoy: Option<&'a BigDecimal>,
+  pub analysttargetprice: Option<&'a BigDecimal>,
+  pub trailingpe: Option<&'a BigDecimal>,
+  pub forwardpe: Option<&'a BigDecimal>,
+  pub pricetosalesratiottm: Option<&'a BigDecimal>,
+  pub pricetobookratio: Option<&'a BigDecimal>,
+  pub evtorevenue: Option<&'a BigDecimal>,
+  pub evtoebitda: Option<&'a BigDecimal>,
+  pub beta: Option<&'a f64>,
+  pub annweekhigh: Option<&'a BigDecimal>,
+  pub annweeklow: Option<&'a BigDecimal>,
+  pub fiftydaymovingaverage: Option<&'a BigDecimal>,
+  pub twohdaymovingaverage: Option<&'a BigDecimal>,
+  pub sharesoutstanding: Option<&'a i64>,
+  pub dividenddate: Option<&'a NaiveDate>,
+  pub exdividenddate: Option<&'a NaiveDate>,
+  #[diesel(skip_update)]
   pub c_time: &'a NaiveDateTime,
   pub mod_time: &'a NaiveDateTime,
 }
@@ -198,27 +220,29 @@ pub struct NewOverviewext<'a> {
 #[derive(Queryable, Debug)]
 pub struct IntraDayPrice {
   pub eventid: i32,
-  pub tstamp: NaiveDateTime,
+  pub tstamp: DateTime<Utc>,
   pub sid: i64,
   pub symbol: String,
-  pub open: f32,
-  pub high: f32,
-  pub low: f32,
-  pub close: f32,
-  pub volume: i32,
+  pub open: BigDecimal,
+  pub high: BigDecimal,
+  pub low: BigDecimal,
+  pub close: BigDecimal,
+  pub volume: i64,
+  pub fractional_volume: Option<BigDecimal>,
 }
 
 #[derive(Insertable, Debug)]
 #[diesel(table_name = intradayprices)]
 pub struct NewIntraDayPrice<'a> {
   pub sid: &'a i64,
-  pub tstamp: &'a NaiveDateTime,
+  pub tstamp: &'a DateTime<Utc>,
   pub symbol: &'a str,
-  pub open: &'a f32,
-  pub high: &'a f32,
-  pub low: &'a f32,
-  pub close: &'a f32,
-  pub volume: &'a i32,
+  pub open: &'a BigDecimal,
+  pub high: &'a BigDecimal,
+  pub low: &'a BigDecimal,
+  pub close: &'a BigDecimal,
+  pub volume: &'a i64,
+  pub fractional_volume: Option<&'a BigDecimal>,
 }
 
 #[derive(Queryable, Debug)]
@@ -227,11 +251,12 @@ pub struct SummaryPrice {
   pub date: NaiveDate,
   pub sid: i64,
   pub symbol: String,
-  pub open: f32,
-  pub high: f32,
-  pub low: f32,
-  pub close: f32,
-  pub volume: i32,
+  pub open: BigDecimal,
+  pub high: BigDecimal,
+  pub low: BigDecimal,
+  pub close: BigDecimal,
+  pub volume: i64,
+  pub fractional_volume: Option<BigDecimal>,
 }
 
 #[derive(Insertable, Debug)]
@@ -240,37 +265,38 @@ pub struct NewSummaryPrice<'a> {
   pub date: &'a NaiveDate,
   pub sid: &'a i64,
   pub symbol: &'a str,
-  pub open: &'a f32,
-  pub high: &'a f32,
-  pub low: &'a f32,
-  pub close: &'a f32,
-  pub volume: &'a i32,
+  pub open: &'a BigDecimal,
+  pub high: &'a BigDecimal,
+  pub low: &'a BigDecimal,
+  pub close: &'a BigDecimal,
+  pub volume: &'a i64,
+  pub fractional_volume: Option<&'a BigDecimal>,
 }
 
 #[derive(Queryable, Debug)]
 pub struct TopStat {
   pub eventid: i32,
-  pub date: NaiveDateTime,
+  pub date: DateTime<Utc>,
   pub event_type: String,
   pub sid: i64,
   pub symbol: String,
-  pub price: f32,
-  pub change_val: f32,
-  pub change_pct: f32,
-  pub volume: i32,
+  pub price: BigDecimal,
+  pub change_val: BigDecimal,
+  pub change_pct: BigDecimal,
+  pub volume: i64,
 }
 
 #[derive(Insertable, Debug)]
 #[diesel(table_name = topstats)]
 pub struct NewTopStat<'a> {
-  pub date: &'a NaiveDateTime,
+  pub date: &'a DateTime<Utc>,
   pub event_type: &'a str,
   pub sid: &'a i64,
   pub symbol: &'a str,
-  pub price: &'a f32,
-  pub change_val: &'a f32,
-  pub change_pct: &'a f32,
-  pub volume: &'a i32,
+  pub price: &'a BigDecimal,
+  pub change_val: &'a BigDecimal,
+  pub change_pct: &'a BigDecimal,
+  pub volume: &'a i64,
 }
 
 #[derive(Queryable, Debug)]
@@ -303,7 +329,9 @@ pub struct NewsOverview {
   pub sid: i64,
   pub items: i32,
   pub hashid: String,
-  pub creation: NaiveDateTime,
+  pub creation: DateTime<Utc>,
+  pub definitionid: Option<i32>,
+  pub last_seen: DateTime<Utc>,
 }
 
 #[derive(Insertable, Debug)]
@@ -312,29 +340,34 @@ pub struct NewNewsOverview<'a> {
   pub sid: i64,
   pub items: &'a i32,
   pub hashid: &'a String,
-  pub creation: &'a NaiveDateTime,
+  pub creation: &'a DateTime<Utc>,
+  pub definitionid: Option<i32>,
 }
 
 #[derive(Queryable, Debug)]
 pub struct Feed {
   pub id: i32,
   pub sid: i64,
-  pub newsoverviewid: i32,
+  pub newsoverviewid: Option<i32>,
   pub articleid: String,
   pub sourceid: i32,
   pub osentiment: f64,
-  pub sentlabel: String,
+  pub sentlabel: SentimentLabel,
+  pub loaded_at: DateTime<Utc>,
+  pub first_seen: DateTime<Utc>,
+  pub last_seen: DateTime<Utc>,
+  pub definitionid: Option<i32>,
 }
 
 #[derive(Insertable, Debug)]
 #[diesel(table_name = feeds)]
 pub struct NewFeed<'a> {
   pub sid: &'a i64,
-  pub newsoverviewid: &'a i32,
+  pub newsoverviewid: Option<i32>,
   pub articleid: &'a String,
   pub sourceid: &'a i32,
   pub osentiment: &'a f64,
-  pub sentlabel: &'a String,
+  pub sentlabel: &'a SentimentLabel,
 }
 
 #[derive(Queryable, Debug)]
@@ -347,7 +380,9 @@ pub struct Article {
   pub summary: String,
   pub banner: String,
   pub author: i32,
-  pub ct: NaiveDateTime,
+  pub ct: DateTime<Utc>,
+  pub canonical_url: Option<String>,
+  pub duplicate_of: Option<String>,
 }
 
 #[derive(Insertable, Debug)]
@@ -361,7 +396,9 @@ pub struct NewArticle<'a> {
   pub summary: &'a String,
   pub banner: &'a String,
   pub author: &'a i32,
-  pub ct: &'a NaiveDateTime,
+  pub ct: &'a DateTime<Utc>,
+  pub canonical_url: &'a String,
+  pub duplicate_of: Option<&'a String>,
 }
 
 #[derive(Queryable, Debug)]
@@ -399,7 +436,8 @@ pub struct TickerSentiment {
   pub sid: i64,
   pub relevance: f64,
   pub tsentiment: f64,
-  pub sentimentlable: String,
+  pub sentimentlable: SentimentLabel,
+  pub definitionid: Option<i32>,
 }
 
 #[derive(Insertable, Debug)]
@@ -409,7 +447,7 @@ pub struct NewTickerSentiment<'a> {
   pub sid: &'a i64,
   pub relevance: &'a f64,
   pub tsentiment: &'a f64,
-  pub sentimentlable: &'a String,
+  pub sentimentlable: &'a SentimentLabel,
 }
 
 #[derive(Queryable, Debug)]
@@ -459,3 +497,60 @@ pub struct NewProcState<'a> {
   pub end_state: &'a i32,
   pub end_time: &'a NaiveDateTime,
 }
+
+#[derive(Queryable, Debug)]
+pub struct SentimentSeries {
+  pub sid: i64,
+  pub granularity: String,
+  pub bucket: DateTime<Utc>,
+  pub articles: i32,
+  pub weighted_sentiment: Option<f64>,
+  pub mean_relevance: f64,
+  pub bullish: i32,
+  pub somewhat_bullish: i32,
+  pub neutral: i32,
+  pub somewhat_bearish: i32,
+  pub bearish: i32,
+  pub sources: i32,
+  pub refreshed_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Debug)]
+pub struct SentimentDefinition {
+  pub id: i32,
+  pub definition: String,
+  pub bearish: f64,
+  pub somewhat_bearish: f64,
+  pub somewhat_bullish: f64,
+  pub bullish: f64,
+  pub created: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = sentimentdefinitions)]
+pub struct NewSentimentDefinition<'a> {
+  pub definition: &'a str,
+  pub bearish: f64,
+  pub somewhat_bearish: f64,
+  pub somewhat_bullish: f64,
+  pub bullish: f64,
+}
+
+#[derive(Queryable, Debug)]
+pub struct PriceLoad {
+  pub sid: i64,
+  pub kind: String,
+  pub first_loaded: DateTime<Utc>,
+  pub last_loaded: DateTime<Utc>,
+  pub last_tstamp: Option<DateTime<Utc>>,
+  pub last_date: Option<NaiveDate>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = priceloads)]
+pub struct NewPriceLoad<'a> {
+  pub sid: &'a i64,
+  pub kind: &'a str,
+  pub last_tstamp: Option<&'a DateTime<Utc>>,
+  pub last_date: Option<&'a NaiveDate>,
+}
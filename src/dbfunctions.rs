@@ -35,14 +35,21 @@ pub mod common;
 pub mod feed;
 
 pub mod combined;
+pub mod migrations;
+pub mod news_quality;
+pub mod news_search;
 pub mod overview;
 pub mod price;
+pub mod price_loads;
 pub mod process;
-pub mod raw_queries;
+pub mod reports;
+pub mod sentiment_labels;
+pub mod sentiment_series;
 pub mod sid;
 pub mod sources;
 pub mod symbols;
 pub mod ticker_sentiments;
 pub mod topic_maps;
 pub mod topic_refs;
+pub mod topic_trends;
 pub mod tops;
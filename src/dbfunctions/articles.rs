@@ -28,10 +28,14 @@
  */
 
 extern crate ring;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use ring::digest::{digest, SHA256};
 
 use crate::{
+  alpha_lib::core::{
+    market_calendar::{to_utc, ALPHA_VANTAGE_TZ},
+    news_identity::{self, article_id, is_near_duplicate},
+  },
   db_models::{Article, NewArticle},
   dbfunctions::common::*,
   schema::articles::dsl::articles,
@@ -64,6 +68,34 @@ pub fn get_article_by_hash(conn: &mut PgConnection, hash_id: String) -> Result<A
     .map_err(Error::from)
 }
 
+/// Returns the publication time of the newest article stored for a sid, or `None` if the sid has
+/// no news yet.
+pub fn get_latest_article_time(
+  conn: &mut PgConnection,
+  s_id: i64,
+) -> Result<Option<DateTime<Utc>>, Error> {
+  use diesel::dsl::max;
+
+  use crate::schema::{articles, feeds};
+
+  feeds::table
+    .inner_join(articles::table)
+    .filter(feeds::sid.eq(s_id))
+    .select(max(articles::ct))
+    .first::<Option<DateTime<Utc>>>(conn)
+    .map_err(Error::from)
+}
+
+/// Window around an article's publication time searched for near-duplicate titles.
+const DUPLICATE_WINDOW_HOURS: i64 = 48;
+
+/// Inserts an article, or returns the stored one if an article with the same canonical url
+/// already exists. An article mentioning several tickers is stored only once.
+///
+/// The article id is the hex SHA256 digest of the canonical url. Articles stored under the
+/// previous `title+url+summary` hash are still found and get their canonical url filled in.
+/// A new article whose title nearly matches one published within two days is linked to it
+/// through `duplicate_of`.
 pub fn insert_article(
   conn: &mut PgConnection,
   s_ourceid: i32,
@@ -75,15 +107,47 @@ pub fn insert_article(
   a_uthor: i32,
   t_published: String,
 ) -> Result<Article, Error> {
+  use crate::schema::articles::dsl::{canonical_url, hashid};
+
   let time_format = "%Y%m%dT%H%M%S";
 
-  let parsed_date =
-    NaiveDateTime::parse_from_str(&t_published, time_format).map_err(Error::from)?;
-  let string_to_hash = format!("{}{}{}", t_itle.clone(), u_rl.clone(), s_ummary.clone());
-  let hash = format!("{:?}", digest(&SHA256, string_to_hash.as_bytes()));
+  let local_date = NaiveDateTime::parse_from_str(&t_published, time_format).map_err(Error::from)?;
+  let parsed_date = to_utc(local_date, ALPHA_VANTAGE_TZ)
+    .ok_or_else(|| Error::UnEx(format!("Invalid publication time {}", t_published)))?;
+  let c_url = news_identity::canonical_url(&u_rl);
+  let id = article_id(&c_url);
+  let legacy_hash = legacy_article_hash(&t_itle, &u_rl, &s_ummary);
+
+  let existing = articles
+    .filter(
+      hashid
+        .eq(&id)
+        .or(canonical_url.eq(&c_url))
+        .or(hashid.eq(&legacy_hash)),
+    )
+    .first::<Article>(conn)
+    .optional()
+    .map_err(Error::from)?;
+  if let Some(article) = existing {
+    if article.canonical_url.is_some() {
+      return Ok(article);
+    }
+    return diesel::update(articles.find(&article.hashid))
+      .set(canonical_url.eq(&c_url))
+      .get_result(conn)
+      .map_err(Error::from);
+  }
 
+  let window = Duration::hours(DUPLICATE_WINDOW_HOURS);
+  let duplicate_of = find_duplicate_of(
+    conn,
+    &t_itle,
+    parsed_date - window,
+    parsed_date + window,
+    None,
+  )?;
   let n_a = NewArticle {
-    hashid: &hash,
+    hashid: &id,
     sourceid: &s_ourceid,
     category: &c_ategory,
     title: &t_itle.clone(),
@@ -92,6 +156,8 @@ pub fn insert_article(
     banner: &b_anner,
     author: &a_uthor,
     ct: &parsed_date,
+    canonical_url: &c_url,
+    duplicate_of: duplicate_of.as_ref(),
   };
 
   diesel::insert_into(articles)
@@ -99,3 +165,84 @@ pub fn insert_article(
     .get_result(conn)
     .map_err(Error::from)
 }
+
+/// Fills `canonical_url` and `duplicate_of` of the articles stored before they existed, oldest
+/// first. Returns the number of articles updated and how many of them were linked.
+///
+/// An article is linked to the earliest article with the same canonical url, or else to an
+/// earlier article of the previous two days whose title nearly matches.
+pub fn backfill_article_identity(conn: &mut PgConnection) -> Result<(usize, usize), Error> {
+  use crate::schema::articles::dsl::{canonical_url, ct, duplicate_of, hashid, title, url};
+
+  let pending = articles
+    .filter(canonical_url.is_null())
+    .order((ct.asc(), hashid.asc()))
+    .select((hashid, title, url, ct))
+    .load::<(String, String, String, DateTime<Utc>)>(conn)?;
+
+  let window = Duration::hours(DUPLICATE_WINDOW_HOURS);
+  let mut linked = 0;
+  for (id, t_itle, u_rl, published) in &pending {
+    let c_url = news_identity::canonical_url(u_rl);
+    let same_url = articles
+      .filter(canonical_url.eq(&c_url))
+      .filter(hashid.ne(id))
+      .order((ct.asc(), hashid.asc()))
+      .select((hashid, duplicate_of))
+      .first::<(String, Option<String>)>(conn)
+      .optional()?;
+    let original = match same_url {
+      Some((other, other_original)) => Some(other_original.unwrap_or(other)),
+      None => find_duplicate_of(conn, t_itle, *published - window, *published, Some(id))?,
+    };
+    if original.is_some() {
+      linked += 1;
+    }
+    diesel::update(articles.find(id))
+      .set((canonical_url.eq(&c_url), duplicate_of.eq(&original)))
+      .execute(conn)?;
+  }
+  Ok((pending.len(), linked))
+}
+
+// `title % x`: the trigram similarity of the titles exceeds `pg_trgm.similarity_threshold`
+diesel::infix_operator!(TrigramSimilar, " % ", backend: diesel::pg::Pg);
+
+/// Returns the id of the earliest original article published between `from` and `to` whose
+/// title nearly matches `title`, other than `exclude`.
+///
+/// The trigram index narrows the window to titles that share most of their trigrams, which
+/// `is_near_duplicate` then compares word by word.
+fn find_duplicate_of(
+  conn: &mut PgConnection,
+  t_itle: &str,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+  exclude: Option<&str>,
+) -> Result<Option<String>, Error> {
+  use diesel::{sql_types::Text, IntoSql};
+
+  use crate::schema::articles::dsl::{ct, duplicate_of, hashid, title};
+
+  let candidates = articles
+    .filter(ct.between(from, to))
+    .filter(duplicate_of.is_null())
+    .filter(TrigramSimilar::new(title, t_itle.into_sql::<Text>()))
+    .filter(hashid.ne(exclude.unwrap_or_default()))
+    .order((ct.asc(), hashid.asc()))
+    .select((hashid, title))
+    .load::<(String, String)>(conn)
+    .map_err(Error::from)?;
+
+  Ok(
+    candidates
+      .into_iter()
+      .find(|(_, other)| is_near_duplicate(t_itle, other))
+      .map(|(id, _)| id),
+  )
+}
+
+fn legacy_article_hash(title: &str, url: &str, summary: &str) -> String {
+  let string_to_hash = format!("{}{}{}", title, url, summary);
+  format!("{:?}", digest(&SHA256, string_to_hash.as_bytes()))
+}
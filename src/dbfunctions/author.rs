@@ -54,6 +54,23 @@ pub fn get_author_by_name(conn: &mut PgConnection, auth_name: String) -> Result<
     .map_err(Error::from)
 }
 
+/// Finds an author by name ignoring case, so differently cased bylines share one author.
+pub fn find_author_by_name(
+  conn: &mut PgConnection,
+  auth_name: &str,
+) -> Result<Option<Author>, Error> {
+  use crate::schema::authors::dsl::author_name;
+
+  diesel::define_sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
+
+  authors
+    .filter(lower(author_name).eq(auth_name.to_lowercase()))
+    .order(crate::schema::authors::dsl::id)
+    .first::<Author>(conn)
+    .optional()
+    .map_err(Error::from)
+}
+
 pub fn get_author_by_id(conn: &mut PgConnection, author_id: i32) -> Result<Author, Error> {
   use crate::schema::authors::dsl::id;
 
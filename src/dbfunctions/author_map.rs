@@ -46,6 +46,10 @@ pub fn insert_author_map(
   feed_id: i32,
   author_id: i32,
 ) -> Result<AuthorMap, Error> {
+  use diesel::upsert::excluded;
+
+  use crate::schema::authormaps::dsl::{authorid, feedid};
+
   let new_author_map = NewAuthorMap {
     feedid: &feed_id,
     authorid: &author_id,
@@ -53,6 +57,9 @@ pub fn insert_author_map(
 
   diesel::insert_into(authormaps)
     .values(&new_author_map)
+    .on_conflict((feedid, authorid))
+    .do_update()
+    .set(authorid.eq(excluded(authorid)))
     .get_result::<AuthorMap>(conn)
     .map_err(Error::from)
 }
@@ -27,54 +27,241 @@
  * SOFTWARE.
  */
 
-use std::{env, process};
+use std::{env, process, str::FromStr, time::Duration};
 
-use diesel::{pg::PgConnection, prelude::*};
+use diesel::{
+  pg::PgConnection,
+  prelude::*,
+  r2d2::{self, ConnectionManager, CustomizeConnection, Pool, PooledConnection},
+};
 use dotenvy::dotenv;
+use thiserror::Error;
 
-/// Establishes a connection to a Postgres database using Diesel.
-///
-/// This function will attempt to read the `DATABASE_URL` environment variable,
-/// which is expected to contain the database connection string.
-/// Ensure that this environment variable is set prior to
-/// invoking this function.
-///
-/// If the `DATABASE_URL` environment variable is not set, the function will
-/// print an error message and terminate the program with a status code of `1`.
-///
-/// If a connection to the database cannot be established,
-/// the function will print an error message and also exit the program with a status code of `1`.
-/// A database connection is crucial for program functionality; without it, the program should not
-/// proceed. The choice to exit instead of panicking is made because this function is ideally
-/// one of the first operations executed in the program.
+use crate::dbfunctions::migrations::check_database;
+
+pub type PgPool = Pool<ConnectionManager<PgConnection>>;
+pub type PgPooledConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error("DATABASE_URL is not set")]
+  MissingUrl,
+  #[error("Invalid value {value:?} for {name}")]
+  InvalidSetting { name: &'static str, value: String },
+  #[error(transparent)]
+  Connection(#[from] ConnectionError),
+  #[error(transparent)]
+  Pool(#[from] r2d2::PoolError),
+}
+
+/// Settings of a connection pool, read from the environment by `PoolConfig::from_env`:
 ///
-/// # Returns
+/// * `DATABASE_URL`: the database to connect to (required)
+/// * `DB_POOL_MAX_SIZE`: the most connections the pool opens (default 10, at least 1)
+/// * `DB_POOL_MIN_IDLE`: the idle connections the pool keeps open (default 1)
+/// * `DB_POOL_TIMEOUT_SECS`: how long to wait for a connection (default 30, at least 1)
+/// * `DB_POOL_IDLE_TIMEOUT_SECS`: when idle connections are closed (default 600, 0 never)
+/// * `DB_TEST_TRANSACTIONS`: `true` runs everything in a transaction that is never committed
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolConfig {
+  pub database_url: String,
+  pub max_size: u32,
+  pub min_idle: u32,
+  pub connection_timeout: Duration,
+  pub idle_timeout: Option<Duration>,
+  /// Every connection begins a test transaction that is rolled back when it closes, so nothing
+  /// is written. The pool is limited to one connection so every checkout sees the same data, and
+  /// that connection is never closed for being idle or old, which would discard the transaction
+  /// mid-run; `idle_timeout` does not apply. Only a broken connection is replaced, with a fresh
+  /// transaction.
+  pub test_transactions: bool,
+}
+
+impl PoolConfig {
+  pub fn new(database_url: &str) -> Self {
+    Self {
+      database_url: database_url.to_string(),
+      max_size: 10,
+      min_idle: 1,
+      connection_timeout: Duration::from_secs(30),
+      idle_timeout: Some(Duration::from_secs(600)),
+      test_transactions: false,
+    }
+  }
+
+  pub fn from_env() -> Result<Self, Error> {
+    dotenv().ok();
+    Self::from_vars(|name| env::var(name).ok())
+  }
+
+  fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, Error> {
+    let setting = |name: &'static str| -> Result<Option<u64>, Error> {
+      var(name)
+        .map(|value| u64::from_str(value.trim()).map_err(|_| Error::InvalidSetting { name, value }))
+        .transpose()
+    };
+    // r2d2 panics on a pool of no connections or a zero connection timeout
+    let positive = |name: &'static str, value: u64| match value {
+      0 => Err(Error::InvalidSetting {
+        name,
+        value: value.to_string(),
+      }),
+      _ => Ok(value),
+    };
+    let narrow = |name: &'static str, value: u64| {
+      u32::try_from(value).map_err(|_| Error::InvalidSetting {
+        name,
+        value: value.to_string(),
+      })
+    };
+
+    let mut config = Self::new(&var("DATABASE_URL").ok_or(Error::MissingUrl)?);
+    if let Some(size) = setting("DB_POOL_MAX_SIZE")? {
+      config.max_size = narrow("DB_POOL_MAX_SIZE", positive("DB_POOL_MAX_SIZE", size)?)?;
+    }
+    if let Some(idle) = setting("DB_POOL_MIN_IDLE")? {
+      config.min_idle = narrow("DB_POOL_MIN_IDLE", idle)?;
+    }
+    if let Some(secs) = setting("DB_POOL_TIMEOUT_SECS")? {
+      config.connection_timeout = Duration::from_secs(positive("DB_POOL_TIMEOUT_SECS", secs)?);
+    }
+    if let Some(secs) = setting("DB_POOL_IDLE_TIMEOUT_SECS")? {
+      config.idle_timeout = (secs > 0).then(|| Duration::from_secs(secs));
+    }
+    if let Some(value) = var("DB_TEST_TRANSACTIONS") {
+      config.test_transactions =
+        bool::from_str(value.trim()).map_err(|_| Error::InvalidSetting {
+          name: "DB_TEST_TRANSACTIONS",
+          value,
+        })?;
+    }
+    Ok(config)
+  }
+
+  /// Builds the pool. Fails if the first `min_idle` connections cannot be opened within the
+  /// connection timeout.
+  pub fn build(&self) -> Result<PgPool, Error> {
+    let builder = Pool::builder().connection_timeout(self.connection_timeout);
+    let builder = match self.test_transactions {
+      true => builder
+        .max_size(1)
+        .min_idle(Some(1))
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connection_customizer(Box::new(TestTransaction)),
+      false => builder
+        .max_size(self.max_size)
+        .min_idle(Some(self.min_idle.min(self.max_size)))
+        .idle_timeout(self.idle_timeout),
+    };
+    builder
+      .build(ConnectionManager::new(&self.database_url))
+      .map_err(Error::from)
+  }
+}
+
+#[derive(Debug)]
+struct TestTransaction;
+
+impl CustomizeConnection<PgConnection, r2d2::Error> for TestTransaction {
+  fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), r2d2::Error> {
+    conn
+      .begin_test_transaction()
+      .map_err(r2d2::Error::QueryError)
+  }
+}
+
+/// Builds a connection pool configured from the environment, see `PoolConfig`.
+pub fn create_pool() -> Result<PgPool, Error> {
+  PoolConfig::from_env()?.build()
+}
+
+/// Opens a single connection to `DATABASE_URL`, outside of any pool.
+pub fn establish_connection() -> Result<PgConnection, Error> {
+  dotenv().ok();
+  let database_url = env::var("DATABASE_URL").map_err(|_| Error::MissingUrl)?;
+  PgConnection::establish(&database_url).map_err(Error::from)
+}
+
+/// Returns a connection from a pool configured from the environment, for binaries, after
+/// checking that the database is migrated and seeded (see `migrations::check_database`).
 ///
-/// On successful connection to the database, this function will return a `PgConnection`.
-/// In the event of any errors, the program will terminate.
+/// A binary cannot do anything without the database, so instead of returning an error this
+/// prints it and exits the program with status `1`. Library code and services should use
+/// `create_pool` and handle the error.
 ///
 /// # Example
 ///```ignore
 /// use dbfunctions::base::establish_connection_or_exit;
 ///
 /// fn main() {
-///     let conn = establish_connection_or_exit();
+///     let conn = &mut establish_connection_or_exit();
 ///     // Operations continue with `conn` or the program will have already exited.
 /// }
 /// ```
-pub fn establish_connection_or_exit() -> PgConnection {
-  dotenv().ok();
-
-  let database_url = match env::var("DATABASE_URL") {
-    Ok(db) => db,
-    Err(_) => {
-      eprintln!("No Database url set");
+pub fn establish_connection_or_exit() -> PgPooledConnection {
+  let mut conn = create_pool()
+    .and_then(|pool| pool.get().map_err(Error::from))
+    .unwrap_or_else(|err| {
+      eprintln!("Can't establish db connection: {}", err);
       process::exit(1);
-    }
-  };
-
-  PgConnection::establish(&database_url).unwrap_or_else(|_| {
-    eprintln!("Can't establish db connection");
+    });
+  if let Err(err) = check_database(&mut conn) {
+    eprintln!("Database is not ready: {}", err);
     process::exit(1);
-  })
+  }
+  conn
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use super::*;
+
+  fn config(vars: &[(&str, &str)]) -> Result<PoolConfig, Error> {
+    let vars: HashMap<String, String> = vars
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect();
+    PoolConfig::from_vars(|name| vars.get(name).cloned())
+  }
+
+  #[test]
+  fn test_pool_config_defaults() {
+    let config = config(&[("DATABASE_URL", "postgres://localhost/av")]).unwrap();
+    assert_eq!(config, PoolConfig::new("postgres://localhost/av"));
+    assert!(matches!(self::config(&[]).unwrap_err(), Error::MissingUrl));
+  }
+
+  #[test]
+  fn test_pool_config_settings() {
+    let config = config(&[
+      ("DATABASE_URL", "postgres://localhost/av"),
+      ("DB_POOL_MAX_SIZE", "4"),
+      ("DB_POOL_MIN_IDLE", "0"),
+      ("DB_POOL_TIMEOUT_SECS", "5"),
+      ("DB_POOL_IDLE_TIMEOUT_SECS", "0"),
+      ("DB_TEST_TRANSACTIONS", "true"),
+    ])
+    .unwrap();
+    assert_eq!(config.max_size, 4);
+    assert_eq!(config.min_idle, 0);
+    assert_eq!(config.connection_timeout, Duration::from_secs(5));
+    assert_eq!(config.idle_timeout, None);
+    assert!(config.test_transactions);
+
+    for (setting, value) in [
+      ("DB_POOL_MAX_SIZE", "ten"),
+      ("DB_POOL_MAX_SIZE", "0"),
+      ("DB_POOL_TIMEOUT_SECS", "0"),
+    ] {
+      let err = self::config(&[
+        ("DATABASE_URL", "postgres://localhost/av"),
+        (setting, value),
+      ])
+      .unwrap_err();
+      assert!(matches!(err, Error::InvalidSetting { name, .. } if name == setting));
+    }
+  }
 }
@@ -28,6 +28,8 @@
  */
 use diesel::PgConnection;
 
+use crate::db_models::Symbol;
+
 /// Fetch the IDs and names of symbols for a specific region and type from the database.
 ///
 /// This function retrieves the IDs (`sid`) and names (`symbol`) of all symbols in the database that
@@ -173,3 +175,36 @@ pub fn get_sids_and_names_with_overview(
     .select((sid, symbol))
     .load::<(i64, String)>(conn)
 }
+
+/// Retrieves the full symbol records for which the overview flag is set to true.
+///
+/// Unlike [`get_sids_and_names_with_overview`] this includes the region and trading hours of each
+/// symbol, which the price loaders need to decide whether the market has traded since their last
+/// run.
+pub fn get_symbols_with_overview(
+  conn: &mut PgConnection,
+) -> Result<Vec<Symbol>, diesel::result::Error> {
+  use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+  use crate::schema::symbols::dsl::{overview, sid, symbols};
+  symbols
+    .filter(overview.eq(true))
+    .order(sid)
+    .load::<Symbol>(conn)
+}
+
+/// Retrieves the IDs and names of all symbols of a security type, e.g. the "Crypto" symbols
+/// created by `process_digital_symbols`.
+pub fn get_sids_and_names_of_type(
+  conn: &mut PgConnection,
+  s_typ: &str,
+) -> Result<Vec<(i64, String)>, diesel::result::Error> {
+  use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+  use crate::schema::symbols::dsl::{sec_type, sid, symbol, symbols};
+  symbols
+    .filter(sec_type.eq(s_typ))
+    .order(sid)
+    .select((sid, symbol))
+    .load::<(i64, String)>(conn)
+}
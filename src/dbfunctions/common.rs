@@ -38,8 +38,8 @@ pub enum Error {
   TimeParse(String),
   #[error(transparent)]
   Diesel(#[from] diesel::result::Error),
-  #[error("No intraday prices found for sid: {0}")]
-  NoData(i64),
+  #[error(transparent)]
+  Json(#[from] serde_json::Error),
   #[error("Unique constraint violation")]
   UniqueViolation,
 }
@@ -26,7 +26,10 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use std::collections::HashSet;
+
 use crate::{
+  alpha_lib::core::sentiment_label::SentimentLabel,
   db_models::{Feed, NewFeed},
   dbfunctions::common::*,
   schema::feeds::dsl::feeds,
@@ -40,18 +43,27 @@ pub enum Error {
   UnEx(String),
 }
 
+/// Inserts the feed row filing an article under a sid. `inp_newsoverviewid` is the snapshot of the
+/// sid's own feed the article came from, none when it came from the feed of another ticker or a
+/// topic. If the article is already filed under the sid, the existing row is returned with its
+/// overall sentiment and load time refreshed. The label is the supplied one again, see
+/// `relabel_sentiments`.
 pub fn insert_feed(
   conn: &mut PgConnection,
   s_id: &i64,
-  inp_newsoverviewid: i32,
+  inp_newsoverviewid: Option<i32>,
   inp_articleid: String,
   inp_sourceid: i32,
   inp_osentiment: f64,
-  inp_sentlabel: String,
+  inp_sentlabel: SentimentLabel,
 ) -> Result<Feed, Error> {
+  use diesel::upsert::excluded;
+
+  use crate::schema::feeds::dsl::{articleid, definitionid, loaded_at, osentiment, sentlabel, sid};
+
   let rt = NewFeed {
     sid: &s_id.clone(),
-    newsoverviewid: &inp_newsoverviewid,
+    newsoverviewid: inp_newsoverviewid,
     articleid: &inp_articleid,
     sourceid: &inp_sourceid,
     osentiment: &inp_osentiment,
@@ -60,6 +72,39 @@ pub fn insert_feed(
 
   diesel::insert_into(feeds)
     .values(&rt)
+    .on_conflict((sid, articleid))
+    .do_update()
+    .set((
+      osentiment.eq(excluded(osentiment)),
+      sentlabel.eq(excluded(sentlabel)),
+      definitionid.eq(None::<i32>),
+      loaded_at.eq(diesel::dsl::now),
+    ))
     .get_result(conn)
     .map_err(Error::from)
 }
+
+/// Moves the `last_seen` time of the articles of a sid with the given canonical urls to now and
+/// returns the canonical urls that were already filed under the sid.
+pub fn mark_articles_seen(
+  conn: &mut PgConnection,
+  s_id: i64,
+  canonical_urls: &[String],
+) -> Result<HashSet<String>, Error> {
+  use crate::schema::{articles, feeds::dsl::*};
+
+  let known = feeds
+    .inner_join(articles::table)
+    .filter(sid.eq(s_id))
+    .filter(articles::canonical_url.eq_any(canonical_urls))
+    .select((articleid, articles::canonical_url))
+    .load::<(String, Option<String>)>(conn)?;
+
+  diesel::update(feeds)
+    .filter(sid.eq(s_id))
+    .filter(articleid.eq_any(known.iter().map(|(article, _)| article)))
+    .set(last_seen.eq(diesel::dsl::now))
+    .execute(conn)?;
+
+  Ok(known.into_iter().filter_map(|(_, url)| url).collect())
+}
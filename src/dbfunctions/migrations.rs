@@ -0,0 +1,287 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! The migrations in `migrations/`, embedded in the library, and the startup check that the
+//! database is at the version `schema.rs` was generated from.
+//!
+//! The migrations up to [`BASELINE_VERSION`] cannot be replayed in order on an empty database,
+//! they were applied to the existing ones by hand. An empty database is created from
+//! `baseline/schema.sql`, the schema at that version, and those migrations are recorded as
+//! applied; the later ones then run as usual.
+
+use diesel::{
+  connection::SimpleConnection, migration::MigrationSource, pg::Pg, sql_types::Bool, Connection,
+  PgConnection,
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use thiserror::Error;
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// The last migration covered by the baseline schema.
+pub const BASELINE_VERSION: &str = "20241008005616";
+
+const BASELINE: &str = include_str!("../../baseline/schema.sql");
+
+/// Process types seeded by the `db_status` migration.
+const PROC_TYPES: [&str; 8] = [
+  "load_intraday",
+  "load_missed",
+  "load_missed_overviews",
+  "load_news",
+  "load_open_close",
+  "load_overviews",
+  "load_symbols",
+  "load_tops",
+];
+
+/// States of the process log, by the ids `process` writes and reads them with.
+const STATES: [(i32, &str); 3] = [(1, "running"), (2, "success"), (3, "failed")];
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error("Migration failed: {0}")]
+  Migration(#[from] Box<dyn std::error::Error + Send + Sync>),
+  #[error(transparent)]
+  Diesel(#[from] diesel::result::Error),
+  #[error("Database is behind, {} pending migrations up to {}; run `migrate up`", .0.len(), .0.last().map_or("", String::as_str))]
+  Pending(Vec<String>),
+  #[error("Database has migrations unknown to this build: {0:?}")]
+  Unknown(Vec<String>),
+  #[error("Table {table} is not seeded, missing {missing:?}")]
+  NotSeeded {
+    table: &'static str,
+    missing: Vec<String>,
+  },
+}
+
+/// A migration known to this build, and whether it has been applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+  pub name: String,
+  pub version: String,
+  pub applied: bool,
+}
+
+/// Versions and names of the embedded migrations, oldest first.
+fn embedded() -> Result<Vec<(String, String)>, Error> {
+  let mut migrations: Vec<(String, String)> = MigrationSource::<Pg>::migrations(&MIGRATIONS)?
+    .iter()
+    .map(|m| (m.name().version().to_string(), m.name().to_string()))
+    .collect();
+  migrations.sort();
+  Ok(migrations)
+}
+
+fn applied(conn: &mut PgConnection) -> Result<Vec<String>, Error> {
+  let mut versions: Vec<String> = conn
+    .applied_migrations()?
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+  versions.sort();
+  Ok(versions)
+}
+
+/// True when no migration has been recorded and the tables of the baseline do not exist.
+fn is_empty(conn: &mut PgConnection) -> Result<bool, Error> {
+  use diesel::{dsl::sql, select, RunQueryDsl};
+
+  if !applied(conn)?.is_empty() {
+    return Ok(false);
+  }
+  Ok(select(sql::<Bool>("to_regclass('symbols') is null")).get_result(conn)?)
+}
+
+/// Creates the baseline schema and records the migrations it covers as applied, returning
+/// their versions.
+fn apply_baseline(conn: &mut PgConnection) -> Result<Vec<String>, Error> {
+  use diesel::{sql_query, sql_types::VarChar, RunQueryDsl};
+
+  let versions: Vec<String> = embedded()?
+    .into_iter()
+    .map(|(version, _)| version)
+    .filter(|version| version.as_str() <= BASELINE_VERSION)
+    .collect();
+  conn.transaction(|conn| {
+    conn.batch_execute(BASELINE)?;
+    for version in &versions {
+      sql_query("insert into __diesel_schema_migrations (version) values ($1)")
+        .bind::<VarChar, _>(version)
+        .execute(conn)?;
+    }
+    Ok::<_, diesel::result::Error>(())
+  })?;
+  Ok(versions)
+}
+
+/// Runs the pending migrations, returning their versions. An empty database is first created
+/// from the baseline schema.
+pub fn run_pending_migrations(conn: &mut PgConnection) -> Result<Vec<String>, Error> {
+  let mut versions = match is_empty(conn)? {
+    true => apply_baseline(conn)?,
+    false => Vec::new(),
+  };
+  versions.extend(
+    conn
+      .run_pending_migrations(MIGRATIONS)?
+      .iter()
+      .map(ToString::to_string),
+  );
+  Ok(versions)
+}
+
+/// Reverts the most recently applied migration, returning its version.
+pub fn revert_last_migration(conn: &mut PgConnection) -> Result<String, Error> {
+  Ok(conn.revert_last_migration(MIGRATIONS)?.to_string())
+}
+
+/// Returns every embedded migration with whether it has been applied, oldest first.
+pub fn get_migration_status(conn: &mut PgConnection) -> Result<Vec<MigrationStatus>, Error> {
+  let applied = applied(conn)?;
+  Ok(
+    embedded()?
+      .into_iter()
+      .map(|(version, name)| MigrationStatus {
+        applied: applied.contains(&version),
+        name,
+        version,
+      })
+      .collect(),
+  )
+}
+
+/// Compares the applied migration versions with the embedded ones.
+fn check_versions(embedded: &[String], applied: &[String]) -> Result<(), Error> {
+  let unknown: Vec<String> = applied
+    .iter()
+    .filter(|v| !embedded.contains(v))
+    .cloned()
+    .collect();
+  if !unknown.is_empty() {
+    return Err(Error::Unknown(unknown));
+  }
+  let pending: Vec<String> = embedded
+    .iter()
+    .filter(|v| !applied.contains(v))
+    .cloned()
+    .collect();
+  match pending.is_empty() {
+    true => Ok(()),
+    false => Err(Error::Pending(pending)),
+  }
+}
+
+/// Returns the entries of `expected` that are not in `found`.
+fn missing<T: PartialEq + ToString>(expected: &[T], found: &[T]) -> Vec<String> {
+  expected
+    .iter()
+    .filter(|e| !found.contains(e))
+    .map(ToString::to_string)
+    .collect()
+}
+
+/// Checks that every embedded migration, and no other, has been applied, and that the
+/// reference tables `proctypes` and `states` are seeded.
+pub fn check_database(conn: &mut PgConnection) -> Result<(), Error> {
+  use diesel::{QueryDsl, RunQueryDsl};
+
+  use crate::schema::{proctypes, states};
+
+  let versions: Vec<String> = embedded()?
+    .into_iter()
+    .map(|(version, _)| version)
+    .collect();
+  check_versions(&versions, &applied(conn)?)?;
+
+  let proc_types: Vec<String> = proctypes::table.select(proctypes::name).load(conn)?;
+  let missing_types = missing(&PROC_TYPES.map(String::from), &proc_types);
+  if !missing_types.is_empty() {
+    return Err(Error::NotSeeded {
+      table: "proctypes",
+      missing: missing_types,
+    });
+  }
+
+  let states: Vec<(i32, String)> = states::table
+    .select((states::id, states::name))
+    .load(conn)?;
+  let expected = STATES.map(|(id, name)| (id, name.to_string()));
+  let missing_states: Vec<String> = expected
+    .iter()
+    .filter(|s| !states.contains(s))
+    .map(|(id, name)| format!("{} {}", id, name))
+    .collect();
+  match missing_states.is_empty() {
+    true => Ok(()),
+    false => Err(Error::NotSeeded {
+      table: "states",
+      missing: missing_states,
+    }),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn versions(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn test_embedded_migrations() {
+    let migrations = embedded().unwrap();
+    assert_eq!(migrations[0].0, "00000000000000");
+    assert!(migrations.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(migrations
+      .iter()
+      .any(|(version, _)| version == BASELINE_VERSION));
+  }
+
+  #[test]
+  fn test_check_versions() {
+    let known = versions(&["1", "2", "3"]);
+    assert!(check_versions(&known, &known).is_ok());
+    assert!(matches!(
+      check_versions(&known, &versions(&["1"])),
+      Err(Error::Pending(p)) if p == versions(&["2", "3"])
+    ));
+    assert!(matches!(
+      check_versions(&known, &versions(&["1", "2", "3", "4"])),
+      Err(Error::Unknown(u)) if u == versions(&["4"])
+    ));
+  }
+
+  #[test]
+  fn test_missing() {
+    assert_eq!(missing(&["a", "b", "c"], &["c", "a"]), versions(&["b"]));
+    assert!(missing(&["a"], &["a", "z"]).is_empty());
+  }
+}
@@ -0,0 +1,287 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Volume, sentiment and price agreement statistics of news sources and authors, for weighting
+//! them in sentiment signals.
+
+use std::{fmt, io, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use diesel::{
+  sql_query,
+  sql_types::{BigInt, Double, Nullable, Text, Timestamptz},
+};
+use serde::Serialize;
+
+use crate::dbfunctions::{common::*, topic_trends::Period};
+
+/// Ticker relevance at or above which a mention counts as highly relevant.
+pub const HIGH_RELEVANCE: f64 = 0.5;
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error(transparent)]
+  DB(#[from] diesel::result::Error),
+  #[error("Unknown grouping {0}")]
+  Grouping(String),
+}
+
+/// Whether statistics are gathered per source or per author.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+  Source,
+  Author,
+}
+
+impl Grouping {
+  /// (name, article) pairs of the grouping. An article with several authors belongs to each.
+  fn owners_sql(&self) -> &'static str {
+    match self {
+      Grouping::Source => {
+        "SELECT so.source_name AS name, a.hashid FROM articles a \
+         INNER JOIN sources so ON so.id = a.sourceid"
+      }
+      Grouping::Author => {
+        "SELECT DISTINCT au.author_name AS name, f.articleid AS hashid FROM authormaps am \
+         INNER JOIN authors au ON au.id = am.authorid INNER JOIN feeds f ON f.id = am.feedid"
+      }
+    }
+  }
+}
+
+impl fmt::Display for Grouping {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Grouping::Source => f.write_str("source"),
+      Grouping::Author => f.write_str("author"),
+    }
+  }
+}
+
+impl FromStr for Grouping {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "source" | "sources" => Ok(Grouping::Source),
+      "author" | "authors" => Ok(Grouping::Author),
+      _ => Err(Error::Grouping(s.to_string())),
+    }
+  }
+}
+
+#[derive(QueryableByName, Debug, Serialize, PartialEq)]
+pub struct QualityStats {
+  #[diesel(sql_type = Text)]
+  pub name: String,
+  #[diesel(sql_type = BigInt)]
+  pub articles: i64,
+  /// Mean and sample deviation of the overall sentiment of the articles.
+  #[diesel(sql_type = Nullable<Double>)]
+  pub mean_sentiment: Option<f64>,
+  #[diesel(sql_type = Nullable<Double>)]
+  pub stddev_sentiment: Option<f64>,
+  /// Ticker mentions of the articles.
+  #[diesel(sql_type = BigInt)]
+  pub mentions: i64,
+  /// Share of the mentions with a relevance of at least [`HIGH_RELEVANCE`].
+  #[diesel(sql_type = Nullable<Double>)]
+  pub high_relevance_share: Option<f64>,
+  /// Bullish or bearish mentions with a known next-day price move.
+  #[diesel(sql_type = BigInt)]
+  pub directional: i64,
+  /// Share of the directional mentions whose label matched the next-day price move.
+  #[diesel(sql_type = Nullable<Double>)]
+  pub agreement: Option<f64>,
+}
+
+#[derive(QueryableByName, Debug, Serialize, PartialEq)]
+pub struct QualityVolume {
+  #[diesel(sql_type = Text)]
+  pub name: String,
+  #[diesel(sql_type = Timestamptz)]
+  pub bucket: DateTime<Utc>,
+  #[diesel(sql_type = BigInt)]
+  pub articles: i64,
+}
+
+/// Returns the statistics of every source or author over the articles published between `from`
+/// and `to`, most prolific first.
+///
+/// The next-day move of a mention runs from the close of the last session that ended before the
+/// article to the close of the following session in `summaryprices`.
+pub fn get_quality_stats(
+  conn: &mut PgConnection,
+  grouping: Grouping,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<QualityStats>, Error> {
+  sql_query(format!(
+    r#"WITH owners AS ({}),
+       owned AS (
+         SELECT o.name, a.hashid, a.ct,
+                ((a.ct AT TIME ZONE 'America/New_York') - interval '16 hours')::date AS close_day
+         FROM owners o
+         INNER JOIN articles a ON a.hashid = o.hashid
+         WHERE a.ct >= $1 AND a.ct < $2
+       ),
+       sentiment AS (
+         SELECT ow.name, AVG(f.osentiment) AS osentiment
+         FROM owned ow
+         INNER JOIN feeds f ON f.articleid = ow.hashid
+         GROUP BY ow.name, ow.hashid
+       ),
+       mentions AS (
+         SELECT ow.name,
+                ts.relevance,
+                CASE ts.sentimentlable
+                  WHEN 'Bullish' THEN 1 WHEN 'Somewhat-Bullish' THEN 1
+                  WHEN 'Bearish' THEN -1 WHEN 'Somewhat-Bearish' THEN -1
+                  ELSE 0
+                END AS direction,
+                sign(next.close - base.close) AS move
+         FROM owned ow
+         INNER JOIN feeds f ON f.articleid = ow.hashid
+         INNER JOIN tickersentiments ts ON ts.feedid = f.id
+         LEFT JOIN LATERAL (
+           SELECT p.close FROM summaryprices p
+           WHERE p.sid = ts.sid AND p.date <= ow.close_day
+           ORDER BY p.date DESC LIMIT 1
+         ) base ON true
+         LEFT JOIN LATERAL (
+           SELECT p.close FROM summaryprices p
+           WHERE p.sid = ts.sid AND p.date > ow.close_day
+           ORDER BY p.date LIMIT 1
+         ) next ON true
+       ),
+       mention_stats AS (
+         SELECT name,
+                COUNT(*) AS mentions,
+                AVG(CASE WHEN relevance >= $3 THEN 1.0 ELSE 0.0 END) AS high_relevance_share,
+                COUNT(*) FILTER (WHERE direction <> 0 AND move <> 0) AS directional,
+                AVG(CASE WHEN direction = move THEN 1.0 ELSE 0.0 END)
+                  FILTER (WHERE direction <> 0 AND move <> 0) AS agreement
+         FROM mentions
+         GROUP BY name
+       )
+       SELECT s.name,
+              COUNT(*) AS articles,
+              AVG(s.osentiment) AS mean_sentiment,
+              STDDEV_SAMP(s.osentiment) AS stddev_sentiment,
+              COALESCE(MAX(m.mentions), 0) AS mentions,
+              MAX(m.high_relevance_share)::float8 AS high_relevance_share,
+              COALESCE(MAX(m.directional), 0) AS directional,
+              MAX(m.agreement)::float8 AS agreement
+       FROM sentiment s
+       LEFT JOIN mention_stats m ON m.name = s.name
+       GROUP BY s.name
+       ORDER BY articles DESC, s.name"#,
+    grouping.owners_sql()
+  ))
+  .bind::<Timestamptz, _>(from)
+  .bind::<Timestamptz, _>(to)
+  .bind::<Double, _>(HIGH_RELEVANCE)
+  .load::<QualityStats>(conn)
+  .map_err(Error::from)
+}
+
+/// Returns the article count of every source or author per period between `from` and `to`.
+pub fn get_quality_volume(
+  conn: &mut PgConnection,
+  grouping: Grouping,
+  period: Period,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<QualityVolume>, Error> {
+  sql_query(format!(
+    r#"WITH owners AS ({})
+       SELECT o.name, date_trunc($3, a.ct, 'America/New_York') AS bucket, COUNT(*) AS articles
+       FROM owners o
+       INNER JOIN articles a ON a.hashid = o.hashid
+       WHERE a.ct >= $1 AND a.ct < $2
+       GROUP BY 1, 2
+       ORDER BY 1, 2"#,
+    grouping.owners_sql()
+  ))
+  .bind::<Timestamptz, _>(from)
+  .bind::<Timestamptz, _>(to)
+  .bind::<Text, _>(period.as_str())
+  .load::<QualityVolume>(conn)
+  .map_err(Error::from)
+}
+
+/// Writes the statistics or volumes as CSV with a header line.
+pub fn write_csv<W: io::Write, T: Serialize>(out: W, rows: &[T]) -> Result<(), csv::Error> {
+  let mut writer = csv::Writer::from_writer(out);
+  for row in rows {
+    writer.serialize(row)?;
+  }
+  writer.flush()?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_grouping() {
+    assert_eq!("Sources".parse::<Grouping>().unwrap(), Grouping::Source);
+    assert_eq!("author".parse::<Grouping>().unwrap(), Grouping::Author);
+    assert!("domain".parse::<Grouping>().is_err());
+  }
+
+  #[test]
+  fn test_write_csv() {
+    let stats = QualityStats {
+      name: "Smith, John".to_string(),
+      articles: 3,
+      mean_sentiment: Some(0.25),
+      stddev_sentiment: None,
+      mentions: 4,
+      high_relevance_share: Some(0.5),
+      directional: 2,
+      agreement: Some(1.0),
+    };
+    let mut out = Vec::new();
+    write_csv(&mut out, &[stats]).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(
+      text.lines().next(),
+      Some(
+        "name,articles,mean_sentiment,stddev_sentiment,mentions,high_relevance_share,directional,\
+         agreement"
+      )
+    );
+    assert_eq!(
+      text.lines().nth(1),
+      Some("\"Smith, John\",3,0.25,,4,0.5,2,1.0")
+    );
+  }
+}
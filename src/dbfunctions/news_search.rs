@@ -0,0 +1,304 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use chrono::{DateTime, Utc};
+use diesel::{
+  sql_query,
+  sql_types::{BigInt, Float4, Text, Timestamptz},
+};
+use serde::Serialize;
+
+use crate::{
+  alpha_lib::core::sentiment_label::SentimentLabel, dbfunctions::common::*,
+  schema::sql_types::SentimentLabel as SentimentLabelType,
+};
+
+/// Number of hits per page when none is requested.
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+/// Largest page a single search returns.
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error(transparent)]
+  DB(#[from] diesel::result::Error),
+  #[error("Invalid search: {0}")]
+  Invalid(String),
+}
+
+/// A search over the stored news articles. Every filter is optional; `text` uses the web search
+/// syntax of Postgres (`"exact phrase"`, `or`, `-excluded`) against titles and summaries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewsSearch {
+  pub text: Option<String>,
+  pub symbol: Option<String>,
+  pub source: Option<String>,
+  pub topic: Option<String>,
+  /// Overall sentiment label of the article.
+  pub sentiment_label: Option<SentimentLabel>,
+  pub from: Option<DateTime<Utc>>,
+  pub to: Option<DateTime<Utc>>,
+  /// 1-based page number.
+  pub page: i64,
+  pub page_size: i64,
+}
+
+impl Default for NewsSearch {
+  fn default() -> Self {
+    NewsSearch {
+      text: None,
+      symbol: None,
+      source: None,
+      topic: None,
+      sentiment_label: None,
+      from: None,
+      to: None,
+      page: 1,
+      page_size: DEFAULT_PAGE_SIZE,
+    }
+  }
+}
+
+#[derive(QueryableByName, Debug, Serialize)]
+pub struct SearchHit {
+  #[diesel(sql_type = Text)]
+  pub hashid: String,
+  #[diesel(sql_type = Text)]
+  pub title: String,
+  #[diesel(sql_type = Text)]
+  pub url: String,
+  #[diesel(sql_type = Text)]
+  pub source_name: String,
+  #[diesel(sql_type = Timestamptz)]
+  pub published: DateTime<Utc>,
+  #[diesel(sql_type = Float4)]
+  pub rank: f32,
+  /// Summary excerpt with the matching words wrapped in `<b>` and `</b>`.
+  #[diesel(sql_type = Text)]
+  pub snippet: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Bind {
+  Text(String),
+  Time(DateTime<Utc>),
+  Int(i64),
+  Label(SentimentLabel),
+}
+
+/// Builds the search statement and the values of its `$n` placeholders, in order.
+fn search_sql(search: &NewsSearch) -> Result<(String, Vec<Bind>), Error> {
+  if search.page < 1 {
+    return Err(Error::Invalid(format!(
+      "page {} must be at least 1",
+      search.page
+    )));
+  }
+  if !(1..=MAX_PAGE_SIZE).contains(&search.page_size) {
+    return Err(Error::Invalid(format!(
+      "page size {} must be between 1 and {}",
+      search.page_size, MAX_PAGE_SIZE
+    )));
+  }
+
+  let mut binds = Vec::new();
+  let mut placeholder = |bind: Bind| {
+    binds.push(bind);
+    format!("${}", binds.len())
+  };
+
+  let text = search
+    .text
+    .as_deref()
+    .map(str::trim)
+    .filter(|t| !t.is_empty());
+  let (rank, snippet, query_from) = match text {
+    Some(text) => {
+      let p = placeholder(Bind::Text(text.to_string()));
+      (
+        "ts_rank(a.search, q)".to_string(),
+        "ts_headline('english', a.summary, q, 'MaxFragments=2, MaxWords=30, MinWords=10')"
+          .to_string(),
+        format!(", websearch_to_tsquery('english', {}) q", p),
+      )
+    }
+    None => (
+      "0::real".to_string(),
+      "left(a.summary, 200)".to_string(),
+      String::new(),
+    ),
+  };
+
+  let mut filters = Vec::new();
+  if !query_from.is_empty() {
+    filters.push("a.search @@ q".to_string());
+  }
+  if let Some(source) = &search.source {
+    filters.push(format!(
+      "so.source_name = {}",
+      placeholder(Bind::Text(source.clone()))
+    ));
+  }
+  if let Some(from) = search.from {
+    filters.push(format!("a.ct >= {}", placeholder(Bind::Time(from))));
+  }
+  if let Some(to) = search.to {
+    filters.push(format!("a.ct < {}", placeholder(Bind::Time(to))));
+  }
+
+  // Symbol, topic and sentiment all describe the same feed entry of the article.
+  let mut feed_joins = String::new();
+  let mut feed_filters = vec!["f.articleid = a.hashid".to_string()];
+  if let Some(symbol) = &search.symbol {
+    feed_joins.push_str(" JOIN symbols s ON s.sid = f.sid");
+    feed_filters.push(format!(
+      "s.symbol = {}",
+      placeholder(Bind::Text(symbol.clone()))
+    ));
+  }
+  if let Some(topic) = &search.topic {
+    feed_joins
+      .push_str(" JOIN topicmaps tm ON tm.feedid = f.id JOIN topicrefs t ON t.id = tm.topicid");
+    feed_filters.push(format!(
+      "t.name = {}",
+      placeholder(Bind::Text(topic.clone()))
+    ));
+  }
+  if let Some(label) = &search.sentiment_label {
+    feed_filters.push(format!(
+      "f.sentlabel = {}",
+      placeholder(Bind::Label(*label))
+    ));
+  }
+  if feed_filters.len() > 1 {
+    filters.push(format!(
+      "EXISTS (SELECT 1 FROM feeds f{} WHERE {})",
+      feed_joins,
+      feed_filters.join(" AND ")
+    ));
+  }
+
+  let where_clause = match filters.is_empty() {
+    true => String::new(),
+    false => format!(" WHERE {}", filters.join(" AND ")),
+  };
+  let limit = placeholder(Bind::Int(search.page_size));
+  let offset = placeholder(Bind::Int((search.page - 1) * search.page_size));
+  let sql = format!(
+    "SELECT a.hashid, a.title, a.url, so.source_name, a.ct AS published, {} AS rank, {} AS snippet \
+     FROM articles a JOIN sources so ON so.id = a.sourceid{}{} \
+     ORDER BY rank DESC, a.ct DESC LIMIT {} OFFSET {}",
+    rank, snippet, query_from, where_clause, limit, offset
+  );
+  Ok((sql, binds))
+}
+
+/// Runs a full-text search over the stored articles, best matches first. Without search text
+/// the filtered articles are returned newest first.
+pub fn search_news(conn: &mut PgConnection, search: &NewsSearch) -> Result<Vec<SearchHit>, Error> {
+  let (sql, binds) = search_sql(search)?;
+  let mut query = sql_query(sql).into_boxed();
+  for bind in binds {
+    query = match bind {
+      Bind::Text(value) => query.bind::<Text, _>(value),
+      Bind::Time(value) => query.bind::<Timestamptz, _>(value),
+      Bind::Int(value) => query.bind::<BigInt, _>(value),
+      Bind::Label(value) => query.bind::<SentimentLabelType, _>(value),
+    };
+  }
+  query.load::<SearchHit>(conn).map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  #[test]
+  fn test_search_sql_text_only() {
+    let search = NewsSearch {
+      text: Some("apple earnings".to_string()),
+      ..Default::default()
+    };
+    let (sql, binds) = search_sql(&search).unwrap();
+    assert!(sql.contains("websearch_to_tsquery('english', $1) q WHERE a.search @@ q"));
+    assert!(sql.ends_with("LIMIT $2 OFFSET $3"));
+    assert!(!sql.contains("EXISTS"));
+    assert_eq!(
+      binds,
+      vec![
+        Bind::Text("apple earnings".to_string()),
+        Bind::Int(DEFAULT_PAGE_SIZE),
+        Bind::Int(0)
+      ]
+    );
+  }
+
+  #[test]
+  fn test_search_sql_filters() {
+    let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let search = NewsSearch {
+      symbol: Some("AAPL".to_string()),
+      topic: Some("Earnings".to_string()),
+      sentiment_label: Some(SentimentLabel::Bullish),
+      from: Some(from),
+      page: 3,
+      page_size: 10,
+      ..Default::default()
+    };
+    let (sql, binds) = search_sql(&search).unwrap();
+    assert!(sql.contains("0::real AS rank"));
+    assert!(sql.contains("a.ct >= $1"));
+    assert!(sql.contains(
+      "EXISTS (SELECT 1 FROM feeds f JOIN symbols s ON s.sid = f.sid JOIN topicmaps tm ON \
+       tm.feedid = f.id JOIN topicrefs t ON t.id = tm.topicid WHERE f.articleid = a.hashid AND \
+       s.symbol = $2 AND t.name = $3 AND f.sentlabel = $4)"
+    ));
+    assert_eq!(binds[0], Bind::Time(from));
+    assert_eq!(binds[4..], [Bind::Int(10), Bind::Int(20)]);
+  }
+
+  #[test]
+  fn test_search_sql_paging() {
+    let blank = NewsSearch {
+      text: Some("  ".to_string()),
+      ..Default::default()
+    };
+    assert!(!search_sql(&blank).unwrap().0.contains("tsquery"));
+    for (page, page_size) in [(0, 10), (1, 0), (1, MAX_PAGE_SIZE + 1)] {
+      let search = NewsSearch {
+        page,
+        page_size,
+        ..Default::default()
+      };
+      assert!(search_sql(&search).is_err());
+    }
+  }
+}
@@ -26,15 +26,20 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use chrono::{DateTime, Utc};
 use diesel::PgConnection;
 
-use crate::{alpha_lib::core::alpha_data_types::FullOverview, dbfunctions::common::Error};
+use crate::{
+  alpha_lib::core::alpha_data_types::FullOverview, db_models::OverviewHistory,
+  dbfunctions::common::Error,
+};
 
-/// Inserts a full overview of a financial entity into the database.
+/// Stores a freshly fetched overview of a financial entity.
 ///
-/// This function takes in a `PgConnection` reference and a `FullOverview` struct.
-/// It then inserts the overview data into two database tables: `overviews` and `overviewexts`.
-/// Additionally, it sets a symbol flag for the given financial entity.
+/// The current overview of the sid in `overviews` and `overviewexts` is inserted, or replaced if
+/// one already exists (its `c_time` is kept), and a snapshot of `full_ov` is appended to
+/// `overview_history` with the fetch time so the evolution of fundamentals can be charted.
+/// Finally the overview flag of the symbol is set. All of it runs in one transaction.
 ///
 /// # Parameters
 ///
@@ -44,8 +49,8 @@ use crate::{alpha_lib::core::alpha_data_types::FullOverview, dbfunctions::common
 ///
 /// # Returns
 ///
-/// * `Result<(), Error>`: Returns an `Ok(())` if the operation is successful. Returns an `Err`
-///   wrapped in a `Box` if any error occurs.
+/// * `Result<(), Error>`: Returns an `Ok(())` if the operation is successful, otherwise the
+///   database or serialization error.
 ///
 /// # Examples
 ///
@@ -58,105 +63,124 @@ use crate::{alpha_lib::core::alpha_data_types::FullOverview, dbfunctions::common
 ///     Err(e) => println!("Error creating overview: {:?}", e),
 /// }
 /// ```
-///
-/// # Panics
-///
-/// * This function will panic if there's an error while saving data into the `overviews` or
-///   `overviewexts` tables.
-/// * This function will also panic if there's an error setting the symbol flag for the provided
-///   `sid` in `FullOverview`.
-///
-/// # ToDo
-///
-/// * Refactor the database insertion code to enhance maintainability.
-/// * Consider returning a custom error type or using more descriptive error handling.
-
 pub fn create_overview(conn: &mut PgConnection, full_ov: FullOverview) -> Result<(), Error> {
-  use chrono::{DateTime, Local};
-  use diesel::RunQueryDsl;
+  use chrono::Local;
+  use diesel::{Connection, RunQueryDsl};
 
   use crate::{
-    db_models::{NewOverview, NewOverviewext},
+    db_models::{NewOverview, NewOverviewHistory, NewOverviewext},
     dbfunctions::symbols,
-    schema::{overviewexts, overviews},
+    schema::{overview_history, overviewexts, overviews},
     security_types::sec_types::SymbolFlag,
   };
 
   let localt: DateTime<Local> = Local::now();
   let now = localt.naive_local(); // NaiveDateTime::now();
+  let fetched_at = Utc::now();
+  let snapshot = serde_json::to_value(&full_ov)?;
 
   let new_overview: NewOverview = NewOverview {
     sid: &full_ov.sid,
     symbol: &full_ov.symbol,
-    name: &full_ov.name,
-    description: &full_ov.description,
-    cik: &full_ov.cik,
-    exch: &full_ov.exch,
-    curr: &full_ov.curr,
-    country: &full_ov.country,
-    sector: &full_ov.sector,
-    industry: &full_ov.industry,
-    address: &full_ov.address,
-    fiscalyearend: &full_ov.fiscalyearend,
-    latestquarter: &full_ov.latestquarter,
-    marketcapitalization: &full_ov.marketcapitalization,
-    ebitda: &full_ov.ebitda,
-    peratio: &full_ov.peratio,
-    pegratio: &full_ov.pegratio,
-    bookvalue: &full_ov.bookvalue,
-    dividendpershare: &full_ov.dividendpershare,
-    dividendyield: &full_ov.dividendyield,
-    eps: &full_ov.eps,
+    name: full_ov.name.as_deref(),
+    description: full_ov.description.as_deref(),
+    cik: full_ov.cik.as_deref(),
+    exch: full_ov.exch.as_deref(),
+    curr: full_ov.curr.as_deref(),
+    country: full_ov.country.as_deref(),
+    sector: full_ov.sector.as_deref(),
+    industry: full_ov.industry.as_deref(),
+    address: full_ov.address.as_deref(),
+    fiscalyearend: full_ov.fiscalyearend.as_deref(),
+    latestquarter: full_ov.latestquarter.as_ref(),
+    marketcapitalization: full_ov.marketcapitalization.as_ref(),
+    ebitda: full_ov.ebitda.as_ref(),
+    peratio: full_ov.peratio.as_ref(),
+    pegratio: full_ov.pegratio.as_ref(),
+    bookvalue: full_ov.bookvalue.as_ref(),
+    dividendpershare: full_ov.dividendpershare.as_ref(),
+    dividendyield: full_ov.dividendyield.as_ref(),
+    eps: full_ov.eps.as_ref(),
     c_time: &now,
     mod_time: &now,
   };
-  //todo: refactor this
-  _ = diesel::insert_into(overviews::table)
-    .values(&new_overview)
-    .execute(conn)
-    .map_err(Error::from);
 
   let new_overviewext: NewOverviewext = NewOverviewext {
-    sid: &full_ov.sid.clone(),
-    revenuepersharettm: &full_ov.revenuepersharettm,
-    profitmargin: &full_ov.profitmargin,
-    operatingmarginttm: &full_ov.operatingmarginttm,
-    returnonassetsttm: &full_ov.returnonassetsttm,
-    returnonequityttm: &full_ov.returnonequityttm,
-    revenuettm: &full_ov.revenuettm,
-    grossprofitttm: &full_ov.grossprofitttm,
-    dilutedepsttm: &full_ov.dilutedepsttm,
-    quarterlyearningsgrowthyoy: &full_ov.quarterlyearningsgrowthyoy,
-    quarterlyrevenuegrowthyoy: &full_ov.quarterlyrevenuegrowthyoy,
-    analysttargetprice: &full_ov.analysttargetprice,
-    trailingpe: &full_ov.trailingpe,
-    forwardpe: &full_ov.forwardpe,
-    pricetosalesratiottm: &full_ov.pricetosalesratiottm,
-    pricetobookratio: &full_ov.pricetobookratio,
-    evtorevenue: &full_ov.evtorevenue,
-    evtoebitda: &full_ov.evtoebitda,
-    beta: &full_ov.beta,
-    annweekhigh: &full_ov.annweekhigh,
-    annweeklow: &full_ov.annweeklow,
-    fiftydaymovingaverage: &full_ov.fiftydaymovingaverage,
-    twohdaymovingaverage: &full_ov.twohdaymovingaverage,
-    sharesoutstanding: &full_ov.sharesoutstanding,
-    dividenddate: &full_ov.dividenddate,
-    exdividenddate: &full_ov.exdividenddate,
+    sid: &full_ov.sid,
+    revenuepersharettm: full_ov.revenuepersharettm.as_ref(),
+    profitmargin: full_ov.profitmargin.as_ref(),
+    operatingmarginttm: full_ov.operatingmarginttm.as_ref(),
+    returnonassetsttm: full_ov.returnonassetsttm.as_ref(),
+    returnonequityttm: full_ov.returnonequityttm.as_ref(),
+    revenuettm: full_ov.revenuettm.as_ref(),
+    grossprofitttm: full_ov.grossprofitttm.as_ref(),
+    dilutedepsttm: full_ov.dilutedepsttm.as_ref(),
+    quarterlyearningsgrowthyoy: full_ov.quarterlyearningsgrowthyoy.as_ref(),
+    quarterlyrevenuegrowthyoy: full_ov.quarterlyrevenuegrowthyoy.as_ref(),
+    analysttargetprice: full_ov.analysttargetprice.as_ref(),
+    trailingpe: full_ov.trailingpe.as_ref(),
+    forwardpe: full_ov.forwardpe.as_ref(),
+    pricetosalesratiottm: full_ov.pricetosalesratiottm.as_ref(),
+    pricetobookratio: full_ov.pricetobookratio.as_ref(),
+    evtorevenue: full_ov.evtorevenue.as_ref(),
+    evtoebitda: full_ov.evtoebitda.as_ref(),
+    beta: full_ov.beta.as_ref(),
+    annweekhigh: full_ov.annweekhigh.as_ref(),
+    annweeklow: full_ov.annweeklow.as_ref(),
+    fiftydaymovingaverage: full_ov.fiftydaymovingaverage.as_ref(),
+    twohdaymovingaverage: full_ov.twohdaymovingaverage.as_ref(),
+    sharesoutstanding: full_ov.sharesoutstanding.as_ref(),
+    dividenddate: full_ov.dividenddate.as_ref(),
+    exdividenddate: full_ov.exdividenddate.as_ref(),
     c_time: &now,
     mod_time: &now,
   };
 
-  //todo: refactor this
-  _ = diesel::insert_into(overviewexts::table)
-    .values(&new_overviewext)
-    .execute(conn)
-    .map(|_| ())
-    .map_err(Error::from);
+  conn.transaction::<_, Error, _>(|conn| {
+    diesel::insert_into(overviews::table)
+      .values(&new_overview)
+      .on_conflict(overviews::sid)
+      .do_update()
+      .set(&new_overview)
+      .execute(conn)?;
+
+    diesel::insert_into(overviewexts::table)
+      .values(&new_overviewext)
+      .on_conflict(overviewexts::sid)
+      .do_update()
+      .set(&new_overviewext)
+      .execute(conn)?;
+
+    diesel::insert_into(overview_history::table)
+      .values(&NewOverviewHistory {
+        sid: &full_ov.sid,
+        fetched_at: &fetched_at,
+        overview: &snapshot,
+      })
+      .execute(conn)?;
+
+    symbols::set_symbol_booleans(conn, full_ov.sid, SymbolFlag::Overview, true)
+  })
+}
+
+/// Returns the overview snapshots of a sid fetched in `[from, to)`, oldest first.
+pub fn get_overview_history(
+  conn: &mut PgConnection,
+  s_id: i64,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<OverviewHistory>, Error> {
+  use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 
-  symbols::set_symbol_booleans(conn, full_ov.sid.clone(), SymbolFlag::Overview, true)?;
+  use crate::schema::overview_history::dsl::*;
 
-  Ok(())
+  overview_history
+    .filter(sid.eq(s_id))
+    .filter(fetched_at.ge(from))
+    .filter(fetched_at.lt(to))
+    .order(fetched_at.asc())
+    .load::<OverviewHistory>(conn)
+    .map_err(Error::from)
 }
 
 pub fn get_full_overview(conn: &mut PgConnection, sym: &str) -> Result<FullOverview, Error> {
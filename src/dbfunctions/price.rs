@@ -26,103 +26,278 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
-use chrono::{NaiveDate, NaiveDateTime};
-use diesel::PgConnection;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{sql_types::BigInt, PgConnection, QueryableByName};
 
 use crate::{
-  alpha_lib::core::alpha_data_types::RawDailyPrice, db_models::IntraDayPrice,
+  alpha_lib::core::{alpha_data_types::RawDailyPrice, decimal::AsF64},
+  db_models::IntraDayPrice,
   dbfunctions::common::Error,
 };
 
-pub fn create_intra_day(conn: &mut PgConnection, tick: IntraDayPrice) -> Result<(), Error> {
-  use diesel::RunQueryDsl;
+/// Rows per multi-row insert, well below the 65535 bind parameters Postgres allows in a statement.
+const INSERT_BATCH: usize = 1000;
+
+/// Upserts the intraday bars of a sid with multi-row inserts and sets its intraday flag once.
+///
+/// A bar already stored for the same (sid, tstamp) is replaced when the vendor revised it and left
+/// alone otherwise. Returns the number of bars inserted or revised.
+pub fn upsert_intraday_prices(
+  conn: &mut PgConnection,
+  s_id: i64,
+  ticks: &[IntraDayPrice],
+) -> Result<usize, Error> {
+  use diesel::{
+    query_dsl::methods::FilterDsl, upsert::excluded, BoolExpressionMethods, Connection,
+    ExpressionMethods, PgExpressionMethods, RunQueryDsl,
+  };
 
   use crate::{
     db_models::NewIntraDayPrice, dbfunctions::symbols, schema::intradayprices,
     security_types::sec_types::SymbolFlag,
   };
 
-  let new_mkt_price = NewIntraDayPrice {
-    sid: &tick.sid,
-    tstamp: &tick.tstamp,
-    symbol: &tick.symbol,
-    open: &tick.open,
-    high: &tick.high,
-    low: &tick.low,
-    close: &tick.close,
-    volume: &tick.volume,
-  };
-  diesel::insert_into(intradayprices::table)
-    .values(&new_mkt_price)
-    .execute(conn)?;
+  if ticks.is_empty() {
+    return Ok(0);
+  }
+  let rows = ticks
+    .iter()
+    .map(|tick| NewIntraDayPrice {
+      sid: &tick.sid,
+      tstamp: &tick.tstamp,
+      symbol: &tick.symbol,
+      open: &tick.open,
+      high: &tick.high,
+      low: &tick.low,
+      close: &tick.close,
+      volume: &tick.volume,
+      fractional_volume: tick.fractional_volume.as_ref(),
+    })
+    .collect::<Vec<_>>();
 
-  symbols::set_symbol_booleans(conn, new_mkt_price.sid.clone(), SymbolFlag::Intraday, true)
-    .map(|_| ())
-    .map_err(Error::from)
+  conn.transaction(|conn| {
+    let mut written = 0;
+    for batch in rows.chunks(INSERT_BATCH) {
+      written += diesel::insert_into(intradayprices::table)
+        .values(batch)
+        .on_conflict((intradayprices::sid, intradayprices::tstamp))
+        .do_update()
+        .set((
+          intradayprices::open.eq(excluded(intradayprices::open)),
+          intradayprices::high.eq(excluded(intradayprices::high)),
+          intradayprices::low.eq(excluded(intradayprices::low)),
+          intradayprices::close.eq(excluded(intradayprices::close)),
+          intradayprices::volume.eq(excluded(intradayprices::volume)),
+          intradayprices::fractional_volume.eq(excluded(intradayprices::fractional_volume)),
+        ))
+        .filter(
+          intradayprices::open
+            .ne(excluded(intradayprices::open))
+            .or(intradayprices::high.ne(excluded(intradayprices::high)))
+            .or(intradayprices::low.ne(excluded(intradayprices::low)))
+            .or(intradayprices::close.ne(excluded(intradayprices::close)))
+            .or(intradayprices::volume.ne(excluded(intradayprices::volume)))
+            .or(
+              intradayprices::fractional_volume
+                .is_distinct_from(excluded(intradayprices::fractional_volume)),
+            ),
+        )
+        .execute(conn)?;
+    }
+    symbols::set_symbol_booleans(conn, s_id, SymbolFlag::Intraday, true)?;
+    Ok(written)
+  })
 }
 
-pub fn insert_open_close(
+/// Upserts the daily prices of a sid with multi-row inserts and sets its summary flag once.
+///
+/// A day already stored for the same (sid, date) is replaced when the vendor revised it and left
+/// alone otherwise. Returns the number of days inserted or revised.
+pub fn upsert_summary_prices(
   conn: &mut PgConnection,
-  symb: &String,
+  symb: &str,
   s_id: i64,
-  open_close: RawDailyPrice,
-) -> Result<(), Error> {
-  use diesel::RunQueryDsl;
+  prices: &[RawDailyPrice],
+) -> Result<usize, Error> {
+  use diesel::{
+    query_dsl::methods::FilterDsl, upsert::excluded, BoolExpressionMethods, Connection,
+    ExpressionMethods, PgExpressionMethods, RunQueryDsl,
+  };
 
   use crate::{
     db_models::NewSummaryPrice, dbfunctions::symbols, schema::summaryprices,
     security_types::sec_types::SymbolFlag,
   };
 
-  let np: NewSummaryPrice = NewSummaryPrice {
-    date: &open_close.date,
-    sid: &s_id,
-    symbol: &symb,
-    open: &open_close.open,
-    high: &open_close.high,
-    low: &open_close.low,
-    close: &open_close.close,
-    volume: &open_close.volume,
-  };
-  diesel::insert_into(summaryprices::table)
-    .values(&np)
-    .execute(conn)?;
-  symbols::set_symbol_booleans(conn, s_id.clone(), SymbolFlag::Summary, true)
-    .map(|_| ())
-    .map_err(Error::from)
+  if prices.is_empty() {
+    return Ok(0);
+  }
+  let rows = prices
+    .iter()
+    .map(|price| NewSummaryPrice {
+      date: &price.date,
+      sid: &s_id,
+      symbol: symb,
+      open: &price.open,
+      high: &price.high,
+      low: &price.low,
+      close: &price.close,
+      volume: &price.volume.whole,
+      fractional_volume: price.volume.fractional.as_ref(),
+    })
+    .collect::<Vec<_>>();
+
+  conn.transaction(|conn| {
+    let mut written = 0;
+    for batch in rows.chunks(INSERT_BATCH) {
+      written += diesel::insert_into(summaryprices::table)
+        .values(batch)
+        .on_conflict((summaryprices::sid, summaryprices::date))
+        .do_update()
+        .set((
+          summaryprices::open.eq(excluded(summaryprices::open)),
+          summaryprices::high.eq(excluded(summaryprices::high)),
+          summaryprices::low.eq(excluded(summaryprices::low)),
+          summaryprices::close.eq(excluded(summaryprices::close)),
+          summaryprices::volume.eq(excluded(summaryprices::volume)),
+          summaryprices::fractional_volume.eq(excluded(summaryprices::fractional_volume)),
+        ))
+        .filter(
+          summaryprices::open
+            .ne(excluded(summaryprices::open))
+            .or(summaryprices::high.ne(excluded(summaryprices::high)))
+            .or(summaryprices::low.ne(excluded(summaryprices::low)))
+            .or(summaryprices::close.ne(excluded(summaryprices::close)))
+            .or(summaryprices::volume.ne(excluded(summaryprices::volume)))
+            .or(
+              summaryprices::fractional_volume
+                .is_distinct_from(excluded(summaryprices::fractional_volume)),
+            ),
+        )
+        .execute(conn)?;
+    }
+    symbols::set_symbol_booleans(conn, s_id, SymbolFlag::Summary, true)?;
+    Ok(written)
+  })
 }
 
-///
-///
-/// todo: get rid of the cut and paste
-pub fn get_intr_day_max_date(conn: &mut PgConnection, s_id: i64) -> Result<NaiveDateTime, Error> {
+/// Returns the intraday closes of a sid as `f64` between `from` and `to` (inclusive), oldest first.
+pub fn get_intraday_closes(
+  conn: &mut PgConnection,
+  s_id: i64,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, f64)>, Error> {
   use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 
-  use crate::schema::intradayprices::dsl::{intradayprices, sid, tstamp};
+  use crate::schema::intradayprices::dsl::{close, intradayprices, sid, tstamp};
 
   intradayprices
     .filter(sid.eq(s_id))
-    .select(tstamp)
-    .order(tstamp.desc())
-    .first::<NaiveDateTime>(conn)
-    .map_err(|err| match err {
-      diesel::result::Error::NotFound => Error::NoData(s_id),
-      other => Error::Diesel(other),
-    })
+    .filter(tstamp.between(from, to))
+    .order(tstamp.asc())
+    .select((tstamp, close))
+    .load::<(DateTime<Utc>, BigDecimal)>(conn)
+    .map(|ticks| ticks.into_iter().map(|(t, c)| (t, c.as_f64())).collect())
+    .map_err(Error::from)
 }
 
-pub fn get_summary_max_date(conn: &mut PgConnection, s_id: i64) -> Result<NaiveDate, Error> {
+/// Returns the daily closes of a sid as `f64` between `from` and `to` (inclusive), oldest first.
+pub fn get_summary_closes(
+  conn: &mut PgConnection,
+  s_id: i64,
+  from: NaiveDate,
+  to: NaiveDate,
+) -> Result<Vec<(NaiveDate, f64)>, Error> {
   use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 
-  use crate::schema::summaryprices::dsl::{date, sid, summaryprices};
+  use crate::schema::summaryprices::dsl::{close, date, sid, summaryprices};
 
   summaryprices
     .filter(sid.eq(s_id))
-    .select(date)
-    .order(date.desc())
-    .first::<NaiveDate>(conn)
-    .map_err(|err| match err {
-      diesel::result::Error::NotFound => Error::NoData(s_id),
-      other => Error::Diesel(other),
-    })
+    .filter(date.between(from, to))
+    .order(date.asc())
+    .select((date, close))
+    .load::<(NaiveDate, BigDecimal)>(conn)
+    .map(|closes| closes.into_iter().map(|(d, c)| (d, c.as_f64())).collect())
+    .map_err(Error::from)
+}
+
+/// The price tables keyed by (sid, time).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceTable {
+  Intraday,
+  Summary,
+}
+
+impl PriceTable {
+  pub const ALL: [PriceTable; 2] = [PriceTable::Intraday, PriceTable::Summary];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      PriceTable::Intraday => "intradayprices",
+      PriceTable::Summary => "summaryprices",
+    }
+  }
+
+  /// The kind of the table's load state in `priceloads`.
+  pub fn kind(self) -> &'static str {
+    match self {
+      PriceTable::Intraday => "intraday",
+      PriceTable::Summary => "summary",
+    }
+  }
+
+  fn time_column(self) -> &'static str {
+    match self {
+      PriceTable::Intraday => "tstamp",
+      PriceTable::Summary => "date",
+    }
+  }
+}
+
+/// The number of surplus rows of a sid, i.e. rows sharing a time with another row.
+#[derive(QueryableByName, Debug)]
+pub struct PriceDuplicates {
+  #[diesel(sql_type = BigInt)]
+  pub sid: i64,
+  #[diesel(sql_type = BigInt)]
+  pub duplicates: i64,
+}
+
+/// Returns the sids of `table` that have several rows for the same time.
+pub fn get_price_duplicates(
+  conn: &mut PgConnection,
+  table: PriceTable,
+) -> Result<Vec<PriceDuplicates>, Error> {
+  use diesel::RunQueryDsl;
+
+  diesel::sql_query(format!(
+    "SELECT sid, COUNT(*) - COUNT(DISTINCT {time}) AS duplicates
+     FROM {table}
+     GROUP BY sid
+     HAVING COUNT(*) > COUNT(DISTINCT {time})
+     ORDER BY sid",
+    table = table.name(),
+    time = table.time_column()
+  ))
+  .load::<PriceDuplicates>(conn)
+  .map_err(Error::from)
+}
+
+/// Deletes the duplicate rows of `table`, keeping the most recently inserted row of every sid and
+/// time. Returns the number of rows deleted.
+pub fn delete_price_duplicates(conn: &mut PgConnection, table: PriceTable) -> Result<usize, Error> {
+  use diesel::RunQueryDsl;
+
+  diesel::sql_query(format!(
+    "DELETE FROM {table} p
+     USING {table} q
+     WHERE p.sid = q.sid AND p.{time} = q.{time} AND p.eventid < q.eventid",
+    table = table.name(),
+    time = table.time_column()
+  ))
+  .execute(conn)
+  .map_err(Error::from)
 }
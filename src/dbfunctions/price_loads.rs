@@ -0,0 +1,169 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Per sid load state of the price tables. A sid without state has never been loaded and needs
+//! a full backfill; once loaded, only the recent bars are fetched.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{
+  sql_types::{Date, Nullable, Timestamptz},
+  PgConnection,
+};
+
+use crate::{
+  db_models::{NewPriceLoad, PriceLoad},
+  dbfunctions::{common::Error, price::PriceTable},
+};
+
+diesel::define_sql_function!(
+  #[sql_name = "greatest"]
+  fn greatest_tstamp(x: Nullable<Timestamptz>, y: Nullable<Timestamptz>) -> Nullable<Timestamptz>
+);
+diesel::define_sql_function!(
+  #[sql_name = "greatest"]
+  fn greatest_date(x: Nullable<Date>, y: Nullable<Date>) -> Nullable<Date>
+);
+
+/// How much history to request from the vendor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+  /// The first load of a sid: the full history the endpoint offers.
+  Backfill,
+  /// A sid loaded before: the latest bars only.
+  Incremental,
+}
+
+impl LoadMode {
+  /// A sid is loaded incrementally once a load stored at least one bar.
+  pub fn for_state(state: Option<&PriceLoad>) -> Self {
+    match state {
+      Some(load) if load.last_tstamp.is_some() || load.last_date.is_some() => LoadMode::Incremental,
+      _ => LoadMode::Backfill,
+    }
+  }
+}
+
+/// Returns the load state of the prices of a sid in `table`, `None` if they were never loaded.
+pub fn get_price_load(
+  conn: &mut PgConnection,
+  s_id: i64,
+  table: PriceTable,
+) -> Result<Option<PriceLoad>, Error> {
+  use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+
+  use crate::schema::priceloads::dsl::{kind, priceloads, sid};
+
+  priceloads
+    .filter(sid.eq(s_id))
+    .filter(kind.eq(table.kind()))
+    .first::<PriceLoad>(conn)
+    .optional()
+    .map_err(Error::from)
+}
+
+/// Records a load of the intraday prices of a sid whose latest bar is `last_tstamp`.
+pub fn record_intraday_load(
+  conn: &mut PgConnection,
+  s_id: i64,
+  last_tstamp: DateTime<Utc>,
+) -> Result<(), Error> {
+  record_price_load(
+    conn,
+    NewPriceLoad {
+      sid: &s_id,
+      kind: PriceTable::Intraday.kind(),
+      last_tstamp: Some(&last_tstamp),
+      last_date: None,
+    },
+  )
+}
+
+/// Records a load of the daily prices of a sid whose latest day is `last_date`.
+pub fn record_summary_load(
+  conn: &mut PgConnection,
+  s_id: i64,
+  last_date: NaiveDate,
+) -> Result<(), Error> {
+  record_price_load(
+    conn,
+    NewPriceLoad {
+      sid: &s_id,
+      kind: PriceTable::Summary.kind(),
+      last_tstamp: None,
+      last_date: Some(&last_date),
+    },
+  )
+}
+
+/// Inserts the state of a first load, or moves `last_loaded` and the latest bar of a later one.
+/// The latest bar never moves back, a load that returned older bars keeps it.
+fn record_price_load(conn: &mut PgConnection, load: NewPriceLoad) -> Result<(), Error> {
+  use diesel::{dsl::now, upsert::excluded, ExpressionMethods, RunQueryDsl};
+
+  use crate::schema::priceloads::dsl::{
+    kind, last_date, last_loaded, last_tstamp, priceloads, sid,
+  };
+
+  diesel::insert_into(priceloads)
+    .values(&load)
+    .on_conflict((sid, kind))
+    .do_update()
+    .set((
+      last_loaded.eq(now),
+      last_tstamp.eq(greatest_tstamp(last_tstamp, excluded(last_tstamp))),
+      last_date.eq(greatest_date(last_date, excluded(last_date))),
+    ))
+    .execute(conn)
+    .map(|_| ())
+    .map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  #[test]
+  fn test_load_mode() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+    let mut load = PriceLoad {
+      sid: 1,
+      kind: PriceTable::Summary.kind().to_string(),
+      first_loaded: now,
+      last_loaded: now,
+      last_tstamp: None,
+      last_date: None,
+    };
+    assert_eq!(LoadMode::for_state(None), LoadMode::Backfill);
+    assert_eq!(LoadMode::for_state(Some(&load)), LoadMode::Backfill);
+    load.last_date = NaiveDate::from_ymd_opt(2024, 1, 2);
+    assert_eq!(LoadMode::for_state(Some(&load)), LoadMode::Incremental);
+  }
+}
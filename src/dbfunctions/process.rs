@@ -26,6 +26,7 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use chrono::NaiveDateTime;
 use diesel::PgConnection;
 
 pub fn get_proc_id(conn: &mut PgConnection, proc_name: &str) -> Result<i32, diesel::result::Error> {
@@ -120,3 +121,20 @@ pub fn log_proc_end(
     .set((end_time.eq(localt), end_state.eq(&e_state)))
     .execute(conn)
 }
+
+/// Returns the start time of the most recent successful run of a process, or `None` if the process
+/// has never completed successfully.
+pub fn get_last_run(
+  conn: &mut PgConnection,
+  pid: i32,
+) -> Result<Option<NaiveDateTime>, diesel::result::Error> {
+  use diesel::{dsl::max, ExpressionMethods, QueryDsl, RunQueryDsl};
+
+  use crate::schema::procstates::dsl::{end_state, proc_id, procstates, start_time};
+
+  procstates
+    .filter(proc_id.eq(pid))
+    .filter(end_state.eq(2))
+    .select(max(start_time))
+    .first::<Option<NaiveDateTime>>(conn)
+}
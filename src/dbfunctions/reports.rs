@@ -0,0 +1,292 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Typed reporting queries over the news and process tables. Every value supplied by a caller is
+//! passed as a bound parameter.
+
+use chrono::{DateTime, Utc};
+use diesel::{
+  sql_query,
+  sql_types::{BigInt, Double, Integer, Nullable, Text},
+};
+use serde::Serialize;
+
+use crate::{alpha_lib::core::sentiment_label::SentimentLabel, dbfunctions::common::*};
+
+diesel::define_sql_function!(fn coalesce(x: Nullable<Text>, y: Text) -> Text);
+
+/// A daily change in mean run time below this share of the mean counts as stable.
+const STABLE_TREND_RATIO: f64 = 0.01;
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error(transparent)]
+  DB(#[from] diesel::result::Error),
+}
+
+#[derive(Queryable, Debug, Serialize, PartialEq)]
+pub struct NewsStory {
+  pub title: String,
+  pub url: String,
+}
+
+#[derive(Queryable, Debug, Serialize, PartialEq)]
+pub struct ArticleCount {
+  pub source_name: String,
+  pub article_count: i64,
+}
+
+/// The ticker sentiment of one story about a sid.
+#[derive(Queryable, Debug, PartialEq)]
+pub struct NewsEvent {
+  pub sid: i64,
+  pub article: String,
+  pub published: DateTime<Utc>,
+  pub label: SentimentLabel,
+  pub relevance: f64,
+  pub sentiment: f64,
+}
+
+/// Ticker sentiment of a symbol for one sentiment label.
+#[derive(Queryable, Debug, Serialize, PartialEq)]
+pub struct SentimentStats {
+  pub label: SentimentLabel,
+  pub count: i64,
+  pub mean_sentiment: Option<f64>,
+  pub mean_relevance: Option<f64>,
+}
+
+/// Run time statistics of a process, in minutes, over its finished runs.
+#[derive(QueryableByName, Debug, Serialize, PartialEq)]
+pub struct ProcStats {
+  #[diesel(sql_type = Integer)]
+  pub proc_id: i32,
+  #[diesel(sql_type = Text)]
+  pub name: String,
+  #[diesel(sql_type = BigInt)]
+  pub runs: i64,
+  #[diesel(sql_type = Double)]
+  pub mean_minutes: f64,
+  #[diesel(sql_type = Nullable<Double>)]
+  pub stddev_minutes: Option<f64>,
+  #[diesel(sql_type = Double)]
+  pub median_minutes: f64,
+  #[diesel(sql_type = Double)]
+  pub min_minutes: f64,
+  #[diesel(sql_type = Double)]
+  pub max_minutes: f64,
+  /// Least squares change of the run time per day, `None` with fewer than two runs.
+  #[diesel(sql_type = Nullable<Double>)]
+  pub slope_per_day: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Trend {
+  Increasing,
+  Decreasing,
+  Stable,
+}
+
+impl ProcStats {
+  /// Direction of the run time, ignoring changes below 1% of the mean per day.
+  pub fn trend(&self) -> Trend {
+    let slope = self.slope_per_day.unwrap_or(0.0);
+    if slope.abs() <= self.mean_minutes.abs() * STABLE_TREND_RATIO {
+      Trend::Stable
+    } else if slope > 0.0 {
+      Trend::Increasing
+    } else {
+      Trend::Decreasing
+    }
+  }
+}
+
+/// Returns the title and url of every article mentioning a symbol, newest first.
+pub fn get_news_stories(conn: &mut PgConnection, sym: &str) -> Result<Vec<NewsStory>, Error> {
+  use crate::schema::{articles, feeds, symbols};
+
+  feeds::table
+    .inner_join(articles::table)
+    .inner_join(symbols::table)
+    .filter(symbols::symbol.eq(sym))
+    .order(articles::ct.desc())
+    .select((articles::title, articles::url))
+    .load::<NewsStory>(conn)
+    .map_err(Error::from)
+}
+
+/// Counts the stories mentioning a symbol. Syndicated copies of an article count once.
+pub fn get_news_count(conn: &mut PgConnection, sym: &str) -> Result<i64, Error> {
+  use diesel::dsl::count;
+
+  use crate::schema::{articles, feeds, symbols};
+
+  feeds::table
+    .inner_join(articles::table)
+    .inner_join(symbols::table)
+    .filter(symbols::symbol.eq(sym))
+    .select(count(coalesce(articles::duplicate_of, articles::hashid)).aggregate_distinct())
+    .first::<i64>(conn)
+    .map_err(Error::from)
+}
+
+/// Counts the stored articles of every source.
+pub fn get_article_counts(conn: &mut PgConnection) -> Result<Vec<ArticleCount>, Error> {
+  use diesel::dsl::count;
+
+  use crate::schema::{articles, sources};
+
+  sources::table
+    .inner_join(articles::table)
+    .group_by(sources::source_name)
+    .order(sources::source_name)
+    .select((sources::source_name, count(articles::hashid)))
+    .load::<ArticleCount>(conn)
+    .map_err(Error::from)
+}
+
+/// Counts the stored articles that are not copies of another article.
+pub fn get_distinct_article_count(conn: &mut PgConnection) -> Result<i64, Error> {
+  use crate::schema::articles::dsl::{articles, duplicate_of};
+
+  articles
+    .filter(duplicate_of.is_null())
+    .count()
+    .get_result::<i64>(conn)
+    .map_err(Error::from)
+}
+
+/// Summarizes the ticker sentiment of a symbol per sentiment label.
+pub fn get_sentiment_stats(
+  conn: &mut PgConnection,
+  sym: &str,
+) -> Result<Vec<SentimentStats>, Error> {
+  use diesel::dsl::{avg, count_star};
+
+  use crate::schema::{symbols, tickersentiments};
+
+  tickersentiments::table
+    .inner_join(symbols::table)
+    .filter(symbols::symbol.eq(sym))
+    .group_by(tickersentiments::sentimentlable)
+    .order(tickersentiments::sentimentlable)
+    .select((
+      tickersentiments::sentimentlable,
+      count_star(),
+      avg(tickersentiments::tsentiment),
+      avg(tickersentiments::relevance),
+    ))
+    .load::<SentimentStats>(conn)
+    .map_err(Error::from)
+}
+
+/// Returns the stories about a sid published between `from` and `to`, oldest first. Of a set
+/// of syndicated copies only the earliest is returned.
+pub fn get_news_events(
+  conn: &mut PgConnection,
+  s_id: i64,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<NewsEvent>, Error> {
+  use crate::schema::{articles, feeds, tickersentiments};
+
+  let story = coalesce(articles::duplicate_of, articles::hashid);
+  let mut events = tickersentiments::table
+    .inner_join(feeds::table.inner_join(articles::table))
+    .filter(tickersentiments::sid.eq(s_id))
+    .filter(articles::ct.between(from, to))
+    .distinct_on(story)
+    .order((story, articles::ct))
+    .select((
+      tickersentiments::sid,
+      articles::hashid,
+      articles::ct,
+      tickersentiments::sentimentlable,
+      tickersentiments::relevance,
+      tickersentiments::tsentiment,
+    ))
+    .load::<NewsEvent>(conn)?;
+  events.sort_by_key(|event| event.published);
+  Ok(events)
+}
+
+/// Returns the run time statistics of every process with a finished run.
+pub fn get_proc_stats(conn: &mut PgConnection) -> Result<Vec<ProcStats>, Error> {
+  sql_query(
+    r#"WITH durations AS (
+         SELECT proc_id,
+                EXTRACT(EPOCH FROM (end_time - start_time))::float8 / 60 AS minutes,
+                EXTRACT(EPOCH FROM start_time)::float8 / 86400 AS day
+         FROM procstates
+         WHERE end_time IS NOT NULL AND proc_id IS NOT NULL
+       )
+       SELECT d.proc_id,
+              t.name,
+              COUNT(*) AS runs,
+              AVG(d.minutes) AS mean_minutes,
+              STDDEV(d.minutes) AS stddev_minutes,
+              PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY d.minutes) AS median_minutes,
+              MIN(d.minutes) AS min_minutes,
+              MAX(d.minutes) AS max_minutes,
+              REGR_SLOPE(d.minutes, d.day) AS slope_per_day
+       FROM durations d
+       INNER JOIN proctypes t ON t.id = d.proc_id
+       GROUP BY d.proc_id, t.name
+       ORDER BY t.name"#,
+  )
+  .load::<ProcStats>(conn)
+  .map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stats(mean_minutes: f64, slope_per_day: Option<f64>) -> ProcStats {
+    ProcStats {
+      proc_id: 1,
+      name: "load_news".to_string(),
+      runs: 10,
+      mean_minutes,
+      stddev_minutes: None,
+      median_minutes: mean_minutes,
+      min_minutes: mean_minutes,
+      max_minutes: mean_minutes,
+      slope_per_day,
+    }
+  }
+
+  #[test]
+  fn test_trend() {
+    assert_eq!(stats(60.0, Some(5.0)).trend(), Trend::Increasing);
+    assert_eq!(stats(60.0, Some(-5.0)).trend(), Trend::Decreasing);
+    assert_eq!(stats(60.0, Some(0.5)).trend(), Trend::Stable);
+    assert_eq!(stats(60.0, None).trend(), Trend::Stable);
+  }
+}
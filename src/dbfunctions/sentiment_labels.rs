@@ -0,0 +1,132 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use diesel::{sql_query, sql_types::Double};
+
+use crate::{
+  alpha_lib::core::sentiment_label::SentimentThresholds,
+  db_models::{NewSentimentDefinition, SentimentDefinition},
+  dbfunctions::common::*,
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error(transparent)]
+  DB(#[from] diesel::result::Error),
+}
+
+/// Returns the id of a sentiment score definition, recording it with its thresholds the first
+/// time it is seen. Feeds labelled under different definitions can then be told apart.
+pub fn get_or_insert_sentiment_definition(
+  conn: &mut PgConnection,
+  def: &str,
+  thresholds: &SentimentThresholds,
+) -> Result<i32, Error> {
+  use crate::schema::sentimentdefinitions::dsl::{definition, id, sentimentdefinitions};
+
+  let new_def = NewSentimentDefinition {
+    definition: def,
+    bearish: thresholds.bearish,
+    somewhat_bearish: thresholds.somewhat_bearish,
+    somewhat_bullish: thresholds.somewhat_bullish,
+    bullish: thresholds.bullish,
+  };
+  diesel::insert_into(sentimentdefinitions)
+    .values(&new_def)
+    .on_conflict(definition)
+    .do_nothing()
+    .execute(conn)?;
+  sentimentdefinitions
+    .filter(definition.eq(def))
+    .select(id)
+    .first::<i32>(conn)
+    .map_err(Error::from)
+}
+
+pub fn get_sentiment_definitions(
+  conn: &mut PgConnection,
+) -> Result<Vec<SentimentDefinition>, Error> {
+  use crate::schema::sentimentdefinitions::dsl::{id, sentimentdefinitions};
+
+  sentimentdefinitions
+    .order(id)
+    .load::<SentimentDefinition>(conn)
+    .map_err(Error::from)
+}
+
+/// Re-derives every stored overall and ticker sentiment label from its score with `thresholds`,
+/// replacing the labels Alpha Vantage supplied. The thresholds are recorded as a sentiment
+/// definition whose id is stored with each relabelled row.
+///
+/// Returns the id of the definition and the number of feed and ticker sentiment rows updated.
+/// The sentiment series count labels, so they must be rebuilt afterwards.
+pub fn relabel_sentiments(
+  conn: &mut PgConnection,
+  thresholds: &SentimentThresholds,
+) -> Result<(i32, usize, usize), Error> {
+  use diesel::{sql_types::Integer, Connection};
+
+  let relabel = |table: &str, label: &str, score: &str| {
+    format!(
+      r#"UPDATE {table} SET {label} = derived.label, definitionid = $5
+         FROM (
+           SELECT id,
+                  CASE
+                    WHEN {score} <= $1 THEN 'Bearish'
+                    WHEN {score} <= $2 THEN 'Somewhat-Bearish'
+                    WHEN {score} < $3 THEN 'Neutral'
+                    WHEN {score} < $4 THEN 'Somewhat-Bullish'
+                    ELSE 'Bullish'
+                  END::sentiment_label AS label
+           FROM {table}
+         ) derived
+         WHERE {table}.id = derived.id
+           AND ({table}.{label} <> derived.label
+                OR {table}.definitionid IS DISTINCT FROM $5)"#
+    )
+  };
+
+  conn.transaction::<_, Error, _>(|conn| {
+    let def_id = get_or_insert_sentiment_definition(conn, &thresholds.definition(), thresholds)?;
+    let mut changed = [0; 2];
+    for (count, (table, label, score)) in changed.iter_mut().zip([
+      ("feeds", "sentlabel", "osentiment"),
+      ("tickersentiments", "sentimentlable", "tsentiment"),
+    ]) {
+      *count = sql_query(relabel(table, label, score))
+        .bind::<Double, _>(thresholds.bearish)
+        .bind::<Double, _>(thresholds.somewhat_bearish)
+        .bind::<Double, _>(thresholds.somewhat_bullish)
+        .bind::<Double, _>(thresholds.bullish)
+        .bind::<Integer, _>(def_id)
+        .execute(conn)?;
+    }
+    Ok((def_id, changed[0], changed[1]))
+  })
+}
@@ -0,0 +1,215 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use diesel::{sql_query, sql_types::Timestamptz};
+
+use crate::{db_models::SentimentSeries, dbfunctions::common::*};
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error(transparent)]
+  DB(#[from] diesel::result::Error),
+  #[error("Unknown granularity {0}")]
+  Granularity(String),
+}
+
+/// Bucket size of a sentiment series. Daily buckets start at midnight US/Eastern so they line up
+/// with the trading days of `summaryprices`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+  Day,
+  Hour,
+}
+
+impl Granularity {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Granularity::Day => "day",
+      Granularity::Hour => "hour",
+    }
+  }
+}
+
+impl fmt::Display for Granularity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for Granularity {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "day" | "daily" => Ok(Granularity::Day),
+      "hour" | "hourly" => Ok(Granularity::Hour),
+      _ => Err(Error::Granularity(s.to_string())),
+    }
+  }
+}
+
+/// The (sid, US/Eastern day) pairs with feed rows loaded after `$1`.
+const TOUCHED_DAYS: &str = r#"
+  SELECT DISTINCT f.sid, date_trunc('day', a.ct, 'America/New_York') AS day
+  FROM feeds f
+  INNER JOIN articles a ON a.hashid = f.articleid
+  WHERE f.loaded_at > $1"#;
+
+/// How far before the high-water mark a refresh looks for feed rows. A feed row carries the start
+/// time of its loader's transaction, which may commit after a refresh already saw later rows.
+const REFRESH_OVERLAP_MINUTES: i64 = 60;
+
+/// Rebuilds the sentiment series of every sid and day that received news since the last refresh,
+/// or since `since` when given. Returns the number of buckets written.
+///
+/// Each refresh records the newest feed load time it processed in `sentimentrefreshes`; the next
+/// one starts `REFRESH_OVERLAP_MINUTES` before it. Rebuilding a day twice is harmless.
+///
+/// Each story counts once per sid: of a set of syndicated copies only the earliest is used.
+pub fn refresh_sentiment_series(
+  conn: &mut PgConnection,
+  since: Option<DateTime<Utc>>,
+) -> Result<usize, Error> {
+  use chrono::Duration;
+  use diesel::{dsl::max, Connection};
+
+  use crate::schema::{
+    feeds::dsl::{feeds, loaded_at},
+    sentimentrefreshes::dsl::{loaded_through, sentimentrefreshes},
+  };
+
+  let watermark = match since {
+    Some(since) => since,
+    None => sentimentrefreshes
+      .select(max(loaded_through))
+      .first::<Option<DateTime<Utc>>>(conn)?
+      .map_or(DateTime::UNIX_EPOCH, |mark| {
+        mark - Duration::minutes(REFRESH_OVERLAP_MINUTES)
+      }),
+  };
+
+  conn.transaction::<_, Error, _>(|conn| {
+    sql_query(format!(
+      r#"WITH touched AS ({})
+         DELETE FROM sentimentseries s
+         USING touched t
+         WHERE s.sid = t.sid AND date_trunc('day', s.bucket, 'America/New_York') = t.day"#,
+      TOUCHED_DAYS
+    ))
+    .bind::<Timestamptz, _>(watermark)
+    .execute(conn)?;
+
+    let written = sql_query(format!(
+      r#"WITH touched AS ({}),
+         stories AS (
+           SELECT DISTINCT ON (ts.sid, COALESCE(a.duplicate_of, a.hashid))
+                  ts.sid, a.ct, a.sourceid, ts.relevance, ts.tsentiment, ts.sentimentlable AS label
+           FROM tickersentiments ts
+           INNER JOIN feeds f ON f.id = ts.feedid
+           INNER JOIN articles a ON a.hashid = f.articleid
+           INNER JOIN touched t
+                   ON t.sid = ts.sid AND t.day = date_trunc('day', a.ct, 'America/New_York')
+           ORDER BY ts.sid, COALESCE(a.duplicate_of, a.hashid), a.ct
+         )
+         INSERT INTO sentimentseries
+           (sid, granularity, bucket, articles, weighted_sentiment, mean_relevance, bullish,
+            somewhat_bullish, neutral, somewhat_bearish, bearish, sources, refreshed_at)
+         SELECT s.sid,
+                g.granularity,
+                CASE g.granularity
+                  WHEN 'day' THEN date_trunc('day', s.ct, 'America/New_York')
+                  ELSE date_trunc('hour', s.ct)
+                END AS bucket,
+                COUNT(*),
+                SUM(s.relevance * s.tsentiment) / NULLIF(SUM(s.relevance), 0),
+                AVG(s.relevance),
+                COUNT(*) FILTER (WHERE s.label = 'Bullish'),
+                COUNT(*) FILTER (WHERE s.label = 'Somewhat-Bullish'),
+                COUNT(*) FILTER (WHERE s.label = 'Neutral'),
+                COUNT(*) FILTER (WHERE s.label = 'Somewhat-Bearish'),
+                COUNT(*) FILTER (WHERE s.label = 'Bearish'),
+                COUNT(DISTINCT s.sourceid),
+                now()
+         FROM stories s
+         CROSS JOIN (VALUES ('day'), ('hour')) AS g (granularity)
+         GROUP BY 1, 2, 3"#,
+      TOUCHED_DAYS
+    ))
+    .bind::<Timestamptz, _>(watermark)
+    .execute(conn)?;
+
+    if let Some(through) = feeds
+      .select(max(loaded_at))
+      .first::<Option<DateTime<Utc>>>(conn)?
+    {
+      diesel::insert_into(sentimentrefreshes)
+        .values(loaded_through.eq(through))
+        .execute(conn)?;
+    }
+    Ok(written)
+  })
+}
+
+/// Returns the sentiment series of a sid between `from` (inclusive) and `to` (exclusive), oldest
+/// bucket first. A daily bucket joins `summaryprices` on
+/// `(bucket AT TIME ZONE 'America/New_York')::date = summaryprices.date`.
+pub fn get_sentiment_series(
+  conn: &mut PgConnection,
+  s_id: i64,
+  granularity: Granularity,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<SentimentSeries>, Error> {
+  use crate::schema::sentimentseries::dsl;
+
+  dsl::sentimentseries
+    .filter(dsl::sid.eq(s_id))
+    .filter(dsl::granularity.eq(granularity.as_str()))
+    .filter(dsl::bucket.ge(from))
+    .filter(dsl::bucket.lt(to))
+    .order(dsl::bucket.asc())
+    .load::<SentimentSeries>(conn)
+    .map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_granularity() {
+    assert_eq!("day".parse::<Granularity>().unwrap(), Granularity::Day);
+    assert_eq!("Hourly".parse::<Granularity>().unwrap(), Granularity::Hour);
+    assert!("week".parse::<Granularity>().is_err());
+    assert_eq!(Granularity::Hour.to_string(), "hour");
+  }
+}
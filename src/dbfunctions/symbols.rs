@@ -238,3 +238,89 @@ pub fn get_symbols_and_sids_for(
     .select((symbol, sid))
     .load::<(String, i64)>(conn)
 }
+
+/// Retrieves the region and time zone recorded for a symbol.
+///
+/// # Returns
+///
+/// A `Result` containing a `(region, timezone)` tuple, or a `diesel::result::Error` if the symbol
+/// does not exist.
+pub fn get_region_and_timezone(
+  conn: &mut PgConnection,
+  s_id: i64,
+) -> Result<(String, String), diesel::result::Error> {
+  use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+  use crate::schema::symbols::dsl::{region, sid, symbols, timezone};
+
+  symbols
+    .filter(sid.eq(s_id))
+    .select((region, timezone))
+    .first::<(String, String)>(conn)
+}
+
+/// Retrieves the sid of a symbol of a given security type, e.g. the "BTC" of type "Crypto".
+///
+/// # Returns
+///
+/// `Ok(None)` if no such symbol exists.
+pub fn get_sid_of_type(
+  conn: &mut PgConnection,
+  sym: &str,
+  s_typ: &str,
+) -> Result<Option<i64>, diesel::result::Error> {
+  use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+
+  use crate::schema::symbols::dsl::{sec_type, sid, symbol, symbols};
+
+  symbols
+    .filter(symbol.eq(sym))
+    .filter(sec_type.eq(s_typ))
+    .select(sid)
+    .first::<i64>(conn)
+    .optional()
+}
+
+/// Returns the sid of a currency, creating an "FX" symbol for it on first use.
+///
+/// Currencies enter the database through the "FOREX:" tickers of the news feed, so they are
+/// created with a 24 hour UTC session like the digital currencies.
+pub fn get_or_create_currency(conn: &mut PgConnection, code: &str) -> Result<i64, Error> {
+  use diesel::{sql_query, sql_types::BigInt, Connection, RunQueryDsl};
+
+  use crate::{dbfunctions::sid::get_next_sid, security_types::sec_types::SecurityType};
+
+  const FX: &str = "FX";
+
+  if let Some(s_id) = get_sid_of_type(conn, code, FX)? {
+    return Ok(s_id);
+  }
+  conn.transaction(|conn| {
+    // Serializes the creation of currencies by concurrent loaders until the commit.
+    sql_query("select pg_advisory_xact_lock($1)")
+      .bind::<BigInt, _>(SecurityType::encode(SecurityType::FX, 0))
+      .execute(conn)?;
+    if let Some(s_id) = get_sid_of_type(conn, code, FX)? {
+      return Ok(s_id);
+    }
+    // get_next_sid starts a type without symbols at the unencoded id 1
+    let next = get_next_sid(conn, FX.to_string())?;
+    let s_id = match SecurityType::get_sec_type(next) {
+      SecurityType::FX => next,
+      _ => SecurityType::encode(SecurityType::FX, next as u32),
+    };
+    let record = AlphaSymbol::new(
+      code.to_string(),
+      code.to_string(),
+      FX.to_string(),
+      "USA".to_string(),
+      "00:00".to_string(),
+      "23:59".to_string(),
+      "UTC".to_string(),
+      code.to_string(),
+      1.0,
+    );
+    create_symbol(conn, s_id, record)?;
+    Ok(s_id)
+  })
+}
@@ -30,6 +30,7 @@
 use diesel::PgConnection;
 
 use crate::{
+  alpha_lib::core::sentiment_label::SentimentLabel,
   db_models::{NewTickerSentiment, TickerSentiment},
   dbfunctions::common::*,
   schema::tickersentiments::dsl::tickersentiments,
@@ -49,18 +50,38 @@ pub fn ins_ticker_sentiment(
   inp_feedid: i32,
   inp_relevance: f64,
   inp_sentiment: f64,
-  inp_sentlabel: String,
+  inp_sentlabel: SentimentLabel,
 ) -> Result<TickerSentiment, Error> {
+  use diesel::upsert::excluded;
+
+  use crate::schema::{
+    feeds::dsl::{feeds, loaded_at},
+    tickersentiments::dsl::{definitionid, feedid, relevance, sentimentlable, sid, tsentiment},
+  };
+
   let rt = NewTickerSentiment {
     sid: &s_id.clone(),
     feedid: &inp_feedid,
     relevance: &inp_relevance,
     tsentiment: &inp_sentiment,
-    sentimentlable: &&inp_sentlabel,
+    sentimentlable: &inp_sentlabel,
   };
 
-  diesel::insert_into(tickersentiments)
+  let sentiment = diesel::insert_into(tickersentiments)
     .values(&rt)
-    .get_result(conn)
-    .map_err(Error::from)
+    .on_conflict((feedid, sid))
+    .do_update()
+    .set((
+      relevance.eq(excluded(relevance)),
+      tsentiment.eq(excluded(tsentiment)),
+      sentimentlable.eq(excluded(sentimentlable)),
+      definitionid.eq(None::<i32>),
+    ))
+    .get_result(conn)?;
+
+  // a revised sentiment rebuilds the series buckets of its feed on the next refresh
+  diesel::update(feeds.find(inp_feedid))
+    .set(loaded_at.eq(diesel::dsl::now))
+    .execute(conn)?;
+  Ok(sentiment)
 }
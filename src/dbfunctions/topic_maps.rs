@@ -49,6 +49,10 @@ pub fn ins_topic_map(
   inp_topicid: i32,
   inp_relscore: f64,
 ) -> Result<TopicMap, Error> {
+  use diesel::{upsert::excluded, ExpressionMethods};
+
+  use crate::schema::topicmaps::dsl::{feedid, relscore, topicid};
+
   let rt = NewTopicMap {
     sid: &inp_sid,
     feedid: &inp_feedid,
@@ -58,6 +62,9 @@ pub fn ins_topic_map(
 
   diesel::insert_into(topicmaps)
     .values(&rt)
+    .on_conflict((feedid, topicid))
+    .do_update()
+    .set(relscore.eq(excluded(relscore)))
     .get_result(conn)
     .map_err(Error::from)
 }
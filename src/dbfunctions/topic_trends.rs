@@ -0,0 +1,347 @@
+/*
+ *
+ *
+ *
+ *
+ * MIT License
+ * Copyright (c) 2024. Dwight J. Browne
+ * dwight[-dot-]browne[-at-]dwightjbrowne[-dot-]com
+ *
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Topic volume, sentiment and share over time, read back from `topicmaps`. Every article counts
+//! once per topic: of a set of syndicated copies only the earliest is used.
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+use diesel::{
+  sql_query,
+  sql_types::{BigInt, Double, Nullable, Text, Timestamptz},
+};
+use serde::Serialize;
+
+use crate::{
+  alpha_lib::core::market_calendar::{to_utc, ALPHA_VANTAGE_TZ},
+  dbfunctions::common::*,
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error(transparent)]
+  DB(#[from] diesel::result::Error),
+  #[error("Unknown period {0}")]
+  Period(String),
+}
+
+/// Length of the time buckets of a topic report. Buckets start at midnight US/Eastern, weeks on
+/// Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+  Day,
+  Week,
+  Month,
+}
+
+impl Period {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Period::Day => "day",
+      Period::Week => "week",
+      Period::Month => "month",
+    }
+  }
+}
+
+impl fmt::Display for Period {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for Period {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "day" | "daily" => Ok(Period::Day),
+      "week" | "weekly" => Ok(Period::Week),
+      "month" | "monthly" => Ok(Period::Month),
+      _ => Err(Error::Period(s.to_string())),
+    }
+  }
+}
+
+#[derive(QueryableByName, Debug, Serialize, PartialEq)]
+pub struct TopicVolume {
+  #[diesel(sql_type = Text)]
+  pub topic: String,
+  #[diesel(sql_type = Timestamptz)]
+  pub bucket: DateTime<Utc>,
+  #[diesel(sql_type = BigInt)]
+  pub articles: i64,
+  /// Mean overall sentiment of the articles.
+  #[diesel(sql_type = Nullable<Double>)]
+  pub mean_sentiment: Option<f64>,
+  #[diesel(sql_type = Nullable<Double>)]
+  pub mean_relevance: Option<f64>,
+}
+
+#[derive(QueryableByName, Debug, Serialize, PartialEq)]
+pub struct TopicSymbol {
+  #[diesel(sql_type = Text)]
+  pub symbol: String,
+  #[diesel(sql_type = BigInt)]
+  pub articles: i64,
+  #[diesel(sql_type = Nullable<Double>)]
+  pub mean_relevance: Option<f64>,
+  #[diesel(sql_type = Nullable<Double>)]
+  pub mean_sentiment: Option<f64>,
+}
+
+/// Share of all articles of a week that carry a topic, against the week before.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TopicShare {
+  pub topic: String,
+  pub articles: i64,
+  pub share: f64,
+  pub previous_articles: i64,
+  pub previous_share: f64,
+}
+
+impl TopicShare {
+  /// Change of the share in percentage points.
+  pub fn change(&self) -> f64 {
+    (self.share - self.previous_share) * 100.0
+  }
+}
+
+/// One row per (topic, story) between `$1` and `$2`.
+const TOPIC_STORIES: &str = r#"
+  SELECT DISTINCT ON (t.name, COALESCE(a.duplicate_of, a.hashid))
+         t.name AS topic, tm.sid, a.ct, f.osentiment, tm.relscore
+  FROM topicmaps tm
+  INNER JOIN topicrefs t ON t.id = tm.topicid
+  INNER JOIN feeds f ON f.id = tm.feedid
+  INNER JOIN articles a ON a.hashid = f.articleid
+  WHERE a.ct >= $1 AND a.ct < $2
+  ORDER BY t.name, COALESCE(a.duplicate_of, a.hashid), a.ct"#;
+
+/// Returns the article count, mean sentiment and mean relevance of every topic per period
+/// between `from` and `to`, oldest bucket first.
+pub fn get_topic_volume(
+  conn: &mut PgConnection,
+  period: Period,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+) -> Result<Vec<TopicVolume>, Error> {
+  sql_query(format!(
+    r#"WITH stories AS ({})
+       SELECT topic,
+              date_trunc($3, ct, 'America/New_York') AS bucket,
+              COUNT(*) AS articles,
+              AVG(osentiment) AS mean_sentiment,
+              AVG(relscore) AS mean_relevance
+       FROM stories
+       GROUP BY 1, 2
+       ORDER BY 2, 1"#,
+    TOPIC_STORIES
+  ))
+  .bind::<Timestamptz, _>(from)
+  .bind::<Timestamptz, _>(to)
+  .bind::<Text, _>(period.as_str())
+  .load::<TopicVolume>(conn)
+  .map_err(Error::from)
+}
+
+/// Returns the symbols with the most articles on a topic between `from` and `to`.
+pub fn get_top_symbols_for_topic(
+  conn: &mut PgConnection,
+  topic: &str,
+  from: DateTime<Utc>,
+  to: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<TopicSymbol>, Error> {
+  sql_query(
+    r#"WITH stories AS (
+         SELECT DISTINCT ON (tm.sid, COALESCE(a.duplicate_of, a.hashid))
+                tm.sid, f.osentiment, tm.relscore
+         FROM topicmaps tm
+         INNER JOIN topicrefs t ON t.id = tm.topicid
+         INNER JOIN feeds f ON f.id = tm.feedid
+         INNER JOIN articles a ON a.hashid = f.articleid
+         WHERE t.name = $1 AND a.ct >= $2 AND a.ct < $3
+         ORDER BY tm.sid, COALESCE(a.duplicate_of, a.hashid), a.ct
+       )
+       SELECT s.symbol,
+              COUNT(*) AS articles,
+              AVG(st.relscore) AS mean_relevance,
+              AVG(st.osentiment) AS mean_sentiment
+       FROM stories st
+       INNER JOIN symbols s ON s.sid = st.sid
+       GROUP BY s.symbol
+       ORDER BY articles DESC, s.symbol
+       LIMIT $4"#,
+  )
+  .bind::<Text, _>(topic)
+  .bind::<Timestamptz, _>(from)
+  .bind::<Timestamptz, _>(to)
+  .bind::<BigInt, _>(limit)
+  .load::<TopicSymbol>(conn)
+  .map_err(Error::from)
+}
+
+/// Returns the topic shares of the week starting at `week` against the week before, largest gain
+/// first. `week` should be a week start as returned by [`week_start`].
+pub fn get_topic_share_changes(
+  conn: &mut PgConnection,
+  week: DateTime<Utc>,
+) -> Result<Vec<TopicShare>, Error> {
+  #[derive(QueryableByName)]
+  struct StoryCount {
+    #[diesel(sql_type = BigInt)]
+    stories: i64,
+  }
+
+  let previous = week_start(week - Duration::days(1));
+  let end = week_start(week + Duration::days(8));
+  let volumes = get_topic_volume(conn, Period::Week, previous, end)?;
+  let mut totals = [0_i64; 2];
+  for (total, (from, to)) in totals.iter_mut().zip([(previous, week), (week, end)]) {
+    *total = sql_query(
+      "SELECT COUNT(DISTINCT COALESCE(duplicate_of, hashid)) AS stories FROM articles \
+       WHERE ct >= $1 AND ct < $2",
+    )
+    .bind::<Timestamptz, _>(from)
+    .bind::<Timestamptz, _>(to)
+    .get_result::<StoryCount>(conn)?
+    .stories;
+  }
+  Ok(topic_shares(&volumes, week, totals[0], totals[1]))
+}
+
+/// Pairs the weekly volumes of each topic for `week` and the week before into shares of the
+/// weekly totals, largest gain first.
+fn topic_shares(
+  volumes: &[TopicVolume],
+  week: DateTime<Utc>,
+  previous_total: i64,
+  total: i64,
+) -> Vec<TopicShare> {
+  let share = |count: i64, total: i64| match total {
+    0 => 0.0,
+    _ => count as f64 / total as f64,
+  };
+
+  let mut shares: Vec<TopicShare> = Vec::new();
+  for volume in volumes {
+    let index = match shares.iter().position(|s| s.topic == volume.topic) {
+      Some(index) => index,
+      None => {
+        shares.push(TopicShare {
+          topic: volume.topic.clone(),
+          articles: 0,
+          share: 0.0,
+          previous_articles: 0,
+          previous_share: 0.0,
+        });
+        shares.len() - 1
+      }
+    };
+    let entry = &mut shares[index];
+    if volume.bucket == week {
+      entry.articles = volume.articles;
+      entry.share = share(volume.articles, total);
+    } else {
+      entry.previous_articles = volume.articles;
+      entry.previous_share = share(volume.articles, previous_total);
+    }
+  }
+  shares.sort_by(|a, b| {
+    b.change()
+      .total_cmp(&a.change())
+      .then(a.topic.cmp(&b.topic))
+  });
+  shares
+}
+
+/// The start of the US/Eastern week (Monday midnight) containing `t`.
+pub fn week_start(t: DateTime<Utc>) -> DateTime<Utc> {
+  let local = t.with_timezone(&ALPHA_VANTAGE_TZ).date_naive();
+  let monday = local - Duration::days(local.weekday().num_days_from_monday() as i64);
+  to_utc(monday.and_hms_opt(0, 0, 0).unwrap(), ALPHA_VANTAGE_TZ).unwrap_or(t)
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  #[test]
+  fn test_period() {
+    assert_eq!("Weekly".parse::<Period>().unwrap(), Period::Week);
+    assert_eq!("month".parse::<Period>().unwrap(), Period::Month);
+    assert!("year".parse::<Period>().is_err());
+  }
+
+  #[test]
+  fn test_week_start() {
+    // Sunday evening in New York is already Monday in UTC.
+    let t = Utc.with_ymd_and_hms(2024, 3, 11, 2, 0, 0).unwrap();
+    assert_eq!(
+      week_start(t),
+      Utc.with_ymd_and_hms(2024, 3, 4, 5, 0, 0).unwrap()
+    );
+    // The week of the DST change starts at midnight EDT.
+    let t = Utc.with_ymd_and_hms(2024, 3, 13, 12, 0, 0).unwrap();
+    assert_eq!(
+      week_start(t),
+      Utc.with_ymd_and_hms(2024, 3, 11, 4, 0, 0).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_topic_shares() {
+    let week = Utc.with_ymd_and_hms(2024, 3, 11, 4, 0, 0).unwrap();
+    let previous = Utc.with_ymd_and_hms(2024, 3, 4, 5, 0, 0).unwrap();
+    let volume = |topic: &str, bucket, articles| TopicVolume {
+      topic: topic.to_string(),
+      bucket,
+      articles,
+      mean_sentiment: None,
+      mean_relevance: None,
+    };
+    let volumes = vec![
+      volume("Earnings", previous, 5),
+      volume("IPO", previous, 5),
+      volume("Earnings", week, 2),
+      volume("IPO", week, 8),
+      volume("Blockchain", week, 1),
+    ];
+    let shares = topic_shares(&volumes, week, 10, 10);
+    let topics: Vec<&str> = shares.iter().map(|s| s.topic.as_str()).collect();
+    assert_eq!(topics, ["IPO", "Blockchain", "Earnings"]);
+    assert!((shares[0].change() - 30.0).abs() < 1e-9);
+    assert_eq!(shares[1].previous_articles, 0);
+    assert!((shares[2].share - 0.2).abs() < 1e-9);
+  }
+}
@@ -26,7 +26,7 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use diesel::PgConnection;
 
 use crate::{alpha_lib::core::alpha_data_types::GTopStat, dbfunctions::common::Error};
@@ -36,7 +36,7 @@ pub fn insert_top_stat(
   s_id: i64,
   ts: GTopStat,
   evt_type: &str,
-  upd_time: NaiveDateTime,
+  upd_time: DateTime<Utc>,
 ) -> Result<(), Error> {
   use diesel::RunQueryDsl;
 
@@ -1,5 +1,11 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+  #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+  #[diesel(postgres_type(name = "sentiment_label"))]
+  pub struct SentimentLabel;
+}
+
 diesel::table! {
     articles (hashid) {
         hashid -> Text,
@@ -10,7 +16,9 @@ diesel::table! {
         summary -> Text,
         banner -> Text,
         author -> Int4,
-        ct -> Timestamp,
+        ct -> Timestamptz,
+        canonical_url -> Nullable<Text>,
+        duplicate_of -> Nullable<Text>,
     }
 }
 
@@ -30,28 +38,36 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::SentimentLabel;
+
     feeds (id) {
         id -> Int4,
         sid -> Int8,
-        newsoverviewid -> Int4,
+        newsoverviewid -> Nullable<Int4>,
         articleid -> Text,
         sourceid -> Int4,
         osentiment -> Float8,
-        sentlabel -> Text,
+        sentlabel -> SentimentLabel,
+        loaded_at -> Timestamptz,
+        first_seen -> Timestamptz,
+        last_seen -> Timestamptz,
+        definitionid -> Nullable<Int4>,
     }
 }
 
 diesel::table! {
     intradayprices (eventid) {
         eventid -> Int4,
-        tstamp -> Timestamp,
+        tstamp -> Timestamptz,
         sid -> Int8,
         symbol -> Text,
-        open -> Float4,
-        high -> Float4,
-        low -> Float4,
-        close -> Float4,
-        volume -> Int4,
+        open -> Numeric,
+        high -> Numeric,
+        low -> Numeric,
+        close -> Numeric,
+        volume -> Int8,
+        fractional_volume -> Nullable<Numeric>,
     }
 }
 
@@ -61,71 +77,93 @@ diesel::table! {
         sid -> Int8,
         items -> Int4,
         hashid -> Text,
-        creation -> Timestamp,
+        creation -> Timestamptz,
+        definitionid -> Nullable<Int4>,
+        last_seen -> Timestamptz,
     }
 }
 
 diesel::table! {
     overviewexts (sid) {
         sid -> Int8,
-        revenuepersharettm -> Float4,
-        profitmargin -> Float4,
-        operatingmarginttm -> Float4,
-        returnonassetsttm -> Float4,
-        returnonequityttm -> Float4,
-        revenuettm -> Int8,
-        grossprofitttm -> Int8,
-        dilutedepsttm -> Float4,
-        quarterlyearningsgrowthyoy -> Float4,
-        quarterlyrevenuegrowthyoy -> Float4,
-        analysttargetprice -> Float4,
-        trailingpe -> Float4,
-        forwardpe -> Float4,
-        pricetosalesratiottm -> Float4,
-        pricetobookratio -> Float4,
-        evtorevenue -> Float4,
-        evtoebitda -> Float4,
-        beta -> Float8,
-        annweekhigh -> Float8,
-        annweeklow -> Float8,
-        fiftydaymovingaverage -> Float8,
-        twohdaymovingaverage -> Float8,
-        sharesoutstanding -> Float8,
-        dividenddate -> Date,
-        exdividenddate -> Date,
+        revenuepersharettm -> Nullable<Numeric>,
+        profitmargin -> Nullable<Numeric>,
+        operatingmarginttm -> Nullable<Numeric>,
+        returnonassetsttm -> Nullable<Numeric>,
+        returnonequityttm -> Nullable<Numeric>,
+        revenuettm -> Nullable<Int8>,
+        grossprofitttm -> Nullable<Int8>,
+        dilutedepsttm -> Nullable<Numeric>,
+        quarterlyearningsgrowthyoy -> Nullable<Numeric>,
+        quarterlyrevenuegrowthyoy -> Nullable<Numeric>,
+        analysttargetprice -> Nullable<Numeric>,
+        trailingpe -> Nullable<Numeric>,
+        forwardpe -> Nullable<Numeric>,
+        pricetosalesratiottm -> Nullable<Numeric>,
+        pricetobookratio -> Nullable<Numeric>,
+        evtorevenue -> Nullable<Numeric>,
+        evtoebitda -> Nullable<Numeric>,
+        beta -> Nullable<Float8>,
+        annweekhigh -> Nullable<Numeric>,
+        annweeklow -> Nullable<Numeric>,
+        fiftydaymovingaverage -> Nullable<Numeric>,
+        twohdaymovingaverage -> Nullable<Numeric>,
+        sharesoutstanding -> Nullable<Int8>,
+        dividenddate -> Nullable<Date>,
+        exdividenddate -> Nullable<Date>,
         c_time -> Timestamp,
         mod_time -> Timestamp,
     }
 }
 
+diesel::table! {
+    overview_history (id) {
+        id -> Int4,
+        sid -> Int8,
+        fetched_at -> Timestamptz,
+        overview -> Jsonb,
+    }
+}
+
 diesel::table! {
     overviews (sid) {
         sid -> Int8,
         symbol -> Text,
-        name -> Text,
-        description -> Text,
-        cik -> Text,
-        exch -> Text,
-        curr -> Text,
-        country -> Text,
-        sector -> Text,
-        industry -> Text,
-        address -> Text,
-        fiscalyearend -> Text,
-        latestquarter -> Date,
-        marketcapitalization -> Int8,
-        ebitda -> Int8,
-        peratio -> Float4,
-        pegratio -> Float4,
-        bookvalue -> Float8,
-        dividendpershare -> Float4,
-        dividendyield -> Float4,
-        eps -> Float4,
+        name -> Nullable<Text>,
+        description -> Nullable<Text>,
+        cik -> Nullable<Text>,
+        exch -> Nullable<Text>,
+        curr -> Nullable<Text>,
+        country -> Nullable<Text>,
+        sector -> Nullable<Text>,
+        industry -> Nullable<Text>,
+        address -> Nullable<Text>,
+        fiscalyearend -> Nullable<Text>,
+        latestquarter -> Nullable<Date>,
+        marketcapitalization -> Nullable<Int8>,
+        ebitda -> Nullable<Int8>,
+        peratio -> Nullable<Numeric>,
+        pegratio -> Nullable<Numeric>,
+        bookvalue -> Nullable<Numeric>,
+        dividendpershare -> Nullable<Numeric>,
+        dividendyield -> Nullable<Numeric>,
+        eps -> Nullable<Numeric>,
         c_time -> Timestamp,
         mod_time -> Timestamp,
     }
 }
 
+diesel::table! {
+    priceloads (sid, kind) {
+        sid -> Int8,
+        kind -> Text,
+        first_loaded -> Timestamptz,
+        last_loaded -> Timestamptz,
+        last_tstamp -> Nullable<Timestamptz>,
+        last_date -> Nullable<Date>,
+    }
+}
+
 diesel::table! {
     procstates (spid) {
         spid -> Int4,
@@ -143,6 +181,44 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    sentimentdefinitions (id) {
+        id -> Int4,
+        definition -> Text,
+        bearish -> Float8,
+        somewhat_bearish -> Float8,
+        somewhat_bullish -> Float8,
+        bullish -> Float8,
+        created -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    sentimentrefreshes (id) {
+        id -> Int4,
+        refreshed_at -> Timestamptz,
+        loaded_through -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    sentimentseries (sid, granularity, bucket) {
+        sid -> Int8,
+        granularity -> Text,
+        bucket -> Timestamptz,
+        articles -> Int4,
+        weighted_sentiment -> Nullable<Float8>,
+        mean_relevance -> Float8,
+        bullish -> Int4,
+        somewhat_bullish -> Int4,
+        neutral -> Int4,
+        somewhat_bearish -> Int4,
+        bearish -> Int4,
+        sources -> Int4,
+        refreshed_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     sources (id) {
         id -> Int4,
@@ -164,11 +240,12 @@ diesel::table! {
         date -> Date,
         sid -> Int8,
         symbol -> Text,
-        open -> Float4,
-        high -> Float4,
-        low -> Float4,
-        close -> Float4,
-        volume -> Int4,
+        open -> Numeric,
+        high -> Numeric,
+        low -> Numeric,
+        close -> Numeric,
+        volume -> Int8,
+        fractional_volume -> Nullable<Numeric>,
     }
 }
 
@@ -192,13 +269,17 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::SentimentLabel;
+
     tickersentiments (id) {
         id -> Int4,
         feedid -> Int4,
         sid -> Int8,
         relevance -> Float8,
         tsentiment -> Float8,
-        sentimentlable -> Text,
+        sentimentlable -> SentimentLabel,
+        definitionid -> Nullable<Int4>,
     }
 }
 
@@ -222,14 +303,14 @@ diesel::table! {
 diesel::table! {
     topstats (eventid) {
         eventid -> Int4,
-        date -> Timestamp,
+        date -> Timestamptz,
         event_type -> Text,
         sid -> Int8,
         symbol -> Text,
-        price -> Float4,
-        change_val -> Float4,
-        change_pct -> Float4,
-        volume -> Int4,
+        price -> Numeric,
+        change_val -> Numeric,
+        change_pct -> Numeric,
+        volume -> Int8,
     }
 }
 
@@ -239,16 +320,22 @@ diesel::joinable!(authormaps -> authors (authorid));
 diesel::joinable!(authormaps -> feeds (feedid));
 diesel::joinable!(feeds -> articles (articleid));
 diesel::joinable!(feeds -> newsoverviews (newsoverviewid));
+diesel::joinable!(feeds -> sentimentdefinitions (definitionid));
 diesel::joinable!(feeds -> sources (sourceid));
 diesel::joinable!(feeds -> symbols (sid));
 diesel::joinable!(intradayprices -> symbols (sid));
+diesel::joinable!(newsoverviews -> sentimentdefinitions (definitionid));
 diesel::joinable!(newsoverviews -> symbols (sid));
+diesel::joinable!(overview_history -> symbols (sid));
 diesel::joinable!(overviewexts -> symbols (sid));
 diesel::joinable!(overviews -> symbols (sid));
+diesel::joinable!(priceloads -> symbols (sid));
 diesel::joinable!(procstates -> proctypes (proc_id));
 diesel::joinable!(procstates -> states (end_state));
+diesel::joinable!(sentimentseries -> symbols (sid));
 diesel::joinable!(summaryprices -> symbols (sid));
 diesel::joinable!(tickersentiments -> feeds (feedid));
+diesel::joinable!(tickersentiments -> sentimentdefinitions (definitionid));
 diesel::joinable!(tickersentiments -> symbols (sid));
 diesel::joinable!(topicmaps -> feeds (feedid));
 diesel::joinable!(topicmaps -> symbols (sid));
@@ -262,10 +349,15 @@ diesel::allow_tables_to_appear_in_same_query!(
   feeds,
   intradayprices,
   newsoverviews,
+  overview_history,
   overviewexts,
   overviews,
+  priceloads,
   procstates,
   proctypes,
+  sentimentdefinitions,
+  sentimentrefreshes,
+  sentimentseries,
   sources,
   states,
   summaryprices,